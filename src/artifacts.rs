@@ -0,0 +1,258 @@
+#![allow(dead_code)]
+
+use crate::metadata::{PackageId, PackageTarget};
+
+use serde::*;
+
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+
+
+/// cargo build --message-format=json<br>
+/// `{ "reason": "...", ... }`
+#[derive(Deserialize, Debug)]
+struct Message {
+    pub reason: String,
+    // ...
+}
+
+/// cargo build --message-format=json<br>
+/// `{ "reason": "compiler-artifact", ... }`
+#[derive(Deserialize, Debug)]
+pub(crate) struct Artifact {
+    pub package_id: PackageId,
+    pub target:     PackageTarget,
+    pub profile:    ArtifactProfile,
+    #[serde(default)] pub features: Vec<String>,
+    pub filenames:  Vec<PathBuf>,
+    pub executable: Option<PathBuf>,
+    // ...
+}
+
+/// cargo build --message-format=json<br>
+/// `{ "reason": "compiler-artifact", "profile": { ... } }`
+#[derive(Deserialize, Debug)]
+pub(crate) struct ArtifactProfile {
+    pub opt_level:  String,
+    pub debuginfo:  serde_json::Value, // 0, 2, "line-tables-only", ...
+    pub test:       bool,
+    // ...
+}
+
+/// cargo build --message-format=json<br>
+/// `{ "reason": "build-script-executed", ... }`
+#[derive(Deserialize, Debug)]
+struct BuildScriptExecuted {
+    pub package_id: PackageId,
+    pub out_dir:    PathBuf,
+    // ...
+}
+
+/// A package's compiled `build-script-build`, as discovered by `cargo build -vv --message-format=json`.
+#[derive(Debug)]
+pub(crate) struct BuildScript {
+    pub package_id: PackageId,
+    pub executable: PathBuf,
+    pub out_dir:    Option<PathBuf>,                // None if the build script failed
+    pub features:   Vec<String>,
+    pub profile:    Option<ArtifactProfile>,        // of the package's other targets, for `OPT_LEVEL` and `DEBUG`
+    pub env:        Option<Vec<(String, String)>>,  // None if the build script was fresh (not rerun, so `-vv` didn't log its environment)
+}
+
+
+
+impl Artifact {
+    /// Run e.g. `cargo test --no-run --message-format=json` with `env`, collecting every `compiler-artifact` message.
+    pub fn probe(args: &[&str], env: &[(String, String)]) -> io::Result<Vec<Self>> {
+        let o = Command::new("cargo").args(args).arg("--message-format=json").envs(env.iter().map(|(k, v)| (k, v))).stderr(Stdio::inherit()).output()?;
+        let desc = format!("`cargo {} --message-format=json`", args.join(" "));
+        match o.status.code() {
+            Some(0) => {},
+            Some(n) => return Err(io::Error::new(io::ErrorKind::BrokenPipe, format!("{} failed (exit code {})", desc, n))),
+            None    => return Err(io::Error::new(io::ErrorKind::BrokenPipe, format!("{} failed (signal)", desc))),
+        }
+        let stdout = String::from_utf8(o.stdout).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+        let mut artifacts = Vec::new();
+        for line in stdout.lines().filter(|line| line.starts_with('{')) {
+            let message : Message = serde_json::from_str(line).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+            if message.reason != "compiler-artifact" { continue }
+            artifacts.push(serde_json::from_str(line).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?);
+        }
+        Ok(artifacts)
+    }
+
+    /// Run `{executable} --list` (as if via `cargo test -- --list`) to enumerate the names of every test in this libtest harness.
+    pub fn list_tests(&self, cwd: &Path) -> io::Result<Vec<String>> {
+        let executable = self.executable.as_ref().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "artifact has no executable"))?;
+        let o = Command::new(executable).arg("--list").current_dir(cwd).stderr(Stdio::inherit()).output()?;
+        let desc = format!("`{} --list`", executable.display());
+        match o.status.code() {
+            Some(0) => {},
+            Some(n) => return Err(io::Error::new(io::ErrorKind::BrokenPipe, format!("{} failed (exit code {})", desc, n))),
+            None    => return Err(io::Error::new(io::ErrorKind::BrokenPipe, format!("{} failed (signal)", desc))),
+        }
+        let stdout = String::from_utf8(o.stdout).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        Ok(stdout.lines().filter_map(|line| line.strip_suffix(": test")).map(String::from).collect())
+    }
+}
+
+impl BuildScript {
+    /// Run e.g. `cargo build -vv --message-format=json`, collecting every build script (and the environment of any that ran.)
+    ///
+    /// A failing `cargo build` is reported, but not an error: a failing build script is what we're trying to debug.
+    pub fn probe(args: &[&str], env: &[(String, String)]) -> io::Result<Vec<Self>> {
+        let o = Command::new("cargo").args(args).args(["-vv", "--message-format=json"]).envs(env.iter().map(|(k, v)| (k, v))).stderr(Stdio::piped()).output()?;
+        let desc = format!("`cargo {} -vv --message-format=json`", args.join(" "));
+        let failed = match o.status.code() {
+            Some(0) => None,
+            Some(n) => Some(format!("{} failed (exit code {})", desc, n)),
+            None    => Some(format!("{} failed (signal)", desc)),
+        };
+        let stderr = String::from_utf8_lossy(&o.stderr);
+        if let Some(failed) = failed {
+            for line in stderr.lines().filter(|line| !line.trim_start().starts_with("Running `")) { // skip `-vv` noise
+                writeln!(io::stderr(), "{}", line)?;
+            }
+            eprintln!("warning: {}, build scripts that failed will still be debuggable", failed);
+        }
+        let stdout = String::from_utf8(o.stdout).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+        let mut scripts = Vec::<Self>::new();
+        let mut profiles = Vec::<(PackageId, ArtifactProfile)>::new();
+        for line in stdout.lines().filter(|line| line.starts_with('{')) {
+            let message : Message = serde_json::from_str(line).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+            match message.reason.as_str() {
+                "compiler-artifact" => {
+                    let artifact : Artifact = serde_json::from_str(line).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+                    if !artifact.target.kind.iter().any(|k| k == "custom-build") {
+                        if !profiles.iter().any(|(id, _)| *id == artifact.package_id) { profiles.push((artifact.package_id, artifact.profile)) }
+                        continue
+                    }
+                    let executable = artifact.filenames.iter().find(|f| f.extension().is_none_or(|ext| ext == "exe"));
+                    if let Some(executable) = executable {
+                        scripts.push(BuildScript { package_id: artifact.package_id, executable: executable.clone(), out_dir: None, features: artifact.features, profile: None, env: None });
+                    }
+                },
+                "build-script-executed" => {
+                    let executed : BuildScriptExecuted = serde_json::from_str(line).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+                    for script in scripts.iter_mut().filter(|s| s.package_id == executed.package_id) {
+                        script.out_dir = Some(executed.out_dir.clone());
+                    }
+                },
+                _other => {},
+            }
+        }
+        for (id, profile) in profiles {
+            if let Some(script) = scripts.iter_mut().find(|s| s.package_id == id) { script.profile = Some(profile) }
+        }
+
+        // e.g. "     Running `CARGO=... OUT_DIR=/.../out TARGET=x86_64-unknown-linux-gnu /.../build-script-build`" (unix)
+        // or   "     Running `set CARGO=...&& set OUT_DIR=C:\...\out&& ... C:\...\build-script-build.exe`" (windows)
+        for line in stderr.lines() {
+            let command = match line.trim_start().strip_prefix("Running `").and_then(|line| line.strip_suffix('`')) {
+                Some(command)   => command,
+                None            => continue,
+            };
+            let (env, program) = parse_running(command);
+            if let Some(script) = scripts.iter_mut().find(|s| s.executable == Path::new(program)) {
+                script.env = Some(env);
+                if script.out_dir.is_none() {
+                    script.out_dir = script.env.iter().flatten().find(|(name, _)| name == "OUT_DIR").map(|(_, value)| PathBuf::from(value));
+                }
+            }
+        }
+        Ok(scripts)
+    }
+}
+
+/// Split a command line logged by cargo (e.g. `cargo build -vv`) into its `(env, program)`, undoing cargo's quoting:
+/// `NAME='value' ... program` on unix, or `set NAME="value"&& ... program` on windows.
+fn parse_running(command: &str) -> (Vec<(String, String)>, &str) {
+    let mut env = Vec::new();
+    let mut rest = command;
+    loop {
+        rest = rest.trim_start_matches(' ');
+        let windows = rest.starts_with("set ");
+        let assignment = if windows { &rest["set ".len()..] } else { rest };
+        let name_len = assignment.find(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '_')).unwrap_or(assignment.len());
+        if name_len == 0 || !assignment[name_len..].starts_with('=') { return (env, rest) } // the (unquoted) program
+        let value = &assignment[name_len+1..];
+        let (value, after) = if windows { unquote_windows(value) } else { unquote_unix(value) };
+        env.push((assignment[..name_len].to_string(), value));
+        rest = if windows { after.strip_prefix("&&").unwrap_or(after) } else { after };
+    }
+}
+
+/// `('...'|\x|x)*` up to the next unquoted space: `(value, rest)`
+fn unquote_unix(s: &str) -> (String, &str) {
+    let mut value = String::new();
+    let mut chars = s.char_indices();
+    while let Some((i, ch)) = chars.next() {
+        match ch {
+            ' '     => return (value, &s[i..]),
+            '\''    => for (_, ch) in chars.by_ref() {
+                if ch == '\'' { break }
+                value.push(ch);
+            },
+            '\\'    => value.extend(chars.next().map(|(_, ch)| ch)),
+            ch      => value.push(ch),
+        }
+    }
+    (value, "")
+}
+
+/// `("..."|x)*` up to the next unquoted `&&` or space, with `CommandLineToArgvW`-style backslashes: `(value, rest)`
+fn unquote_windows(s: &str) -> (String, &str) {
+    let mut value = String::new();
+    let mut quoted = false;
+    let mut chars = s.char_indices().peekable();
+    while let Some((i, ch)) = chars.next() {
+        match ch {
+            ' '                                         if !quoted => return (value, &s[i..]),
+            '&' if s[i..].starts_with("&&")             && !quoted => return (value, &s[i..]),
+            '"' => quoted = !quoted,
+            '\\' => {
+                let mut backslashes = 1;
+                while chars.next_if(|(_, ch)| *ch == '\\').is_some() { backslashes += 1 }
+                if chars.peek().is_some_and(|(_, ch)| *ch == '"') {
+                    value.extend(std::iter::repeat_n('\\', backslashes / 2));
+                    if backslashes % 2 == 1 { chars.next(); value.push('"') } // else the '"' (un)quotes
+                } else {
+                    value.extend(std::iter::repeat_n('\\', backslashes));
+                }
+            },
+            ch => value.push(ch),
+        }
+    }
+    (value, "")
+}
+
+
+
+#[cfg(test)] mod tests {
+    use super::*;
+
+    fn env(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    #[test] fn parse_running_unix() {
+        let (vars, program) = parse_running("CARGO=/bin/cargo CARGO_PKG_AUTHORS='' CARGO_PKG_DESCRIPTION='it'\\''s a test' NUM_JOBS=1 OUT_DIR=/t/build/app-2/out /t/build/app-1/build-script-build");
+        assert_eq!(vars, env(&[("CARGO", "/bin/cargo"), ("CARGO_PKG_AUTHORS", ""), ("CARGO_PKG_DESCRIPTION", "it's a test"), ("NUM_JOBS", "1"), ("OUT_DIR", "/t/build/app-2/out")]));
+        assert_eq!(program, "/t/build/app-1/build-script-build");
+    }
+
+    #[test] fn parse_running_windows() {
+        let (vars, program) = parse_running(r#"set CARGO=C:\cargo\bin\cargo.exe&& set CARGO_PKG_AUTHORS=""&& set CARGO_PKG_DESCRIPTION="say \"hi\""&& set PATH="C:\Program Files\x;C:\y\\"&& set OUT_DIR=C:\t\build\app-2\out&& C:\Users\Some One\t\build\app-1\build-script-build.exe"#);
+        assert_eq!(vars, env(&[("CARGO", r"C:\cargo\bin\cargo.exe"), ("CARGO_PKG_AUTHORS", ""), ("CARGO_PKG_DESCRIPTION", r#"say "hi""#), ("PATH", r"C:\Program Files\x;C:\y\"), ("OUT_DIR", r"C:\t\build\app-2\out")]));
+        assert_eq!(program, r"C:\Users\Some One\t\build\app-1\build-script-build.exe");
+    }
+
+    #[test] fn parse_running_no_env() {
+        assert_eq!(parse_running("/t/build-script-build"), (Vec::new(), "/t/build-script-build"));
+        assert_eq!(parse_running(r"C:\t\build-script-build.exe"), (Vec::new(), r"C:\t\build-script-build.exe"));
+    }
+}
@@ -0,0 +1,260 @@
+#![allow(dead_code)]
+
+use serde::*;
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+
+
+/// .cargo/config.toml<br>
+/// (merged from every config file cargo would read)
+#[derive(Deserialize, Debug, Default)]
+pub(crate) struct Config {
+    #[serde(default)] pub build:  Build,
+    #[serde(default)] pub target: BTreeMap<String, Target>,
+    #[serde(default)] pub env:    BTreeMap<String, EnvValue>,
+    // ...
+}
+
+/// .cargo/config.toml<br>
+///
+/// ```toml
+/// [build]
+/// target = "x86_64-unknown-linux-musl" # or ["...", "..."]
+/// ```
+#[derive(Deserialize, Debug, Default)]
+pub(crate) struct Build {
+    pub target: Option<StringOrVec>,
+    // ...
+}
+
+/// .cargo/config.toml<br>
+///
+/// ```toml
+/// [target.aarch64-unknown-linux-gnu]
+/// runner = "qemu-aarch64 -L /usr/aarch64-linux-gnu" # or ["...", "..."]
+/// ```
+#[derive(Deserialize, Debug, Default)]
+pub(crate) struct Target {
+    pub runner: Option<StringOrVec>,
+    // ...
+}
+
+/// .cargo/config.toml<br>
+///
+/// ```toml
+/// [env]
+/// RUST_LOG = "debug" # or:
+/// ASSETS = { value = "assets", relative = true, force = true }
+/// ```
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub(crate) enum EnvValue {
+    String(String),
+    Table { value: String, #[serde(default)] relative: bool, #[serde(default)] force: bool },
+}
+
+/// `"a b c"` or `["a", "b", "c"]`
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub(crate) enum StringOrVec {
+    String(String),
+    Vec(Vec<String>),
+}
+
+
+
+impl Config {
+    /// Read and merge `.cargo/config.toml` files from `dir` and its ancestors, then `$CARGO_HOME/config.toml`, as cargo would.
+    pub fn read(dir: &Path) -> io::Result<Self> {
+        let mut config = Config::default();
+        for dir in dir.ancestors().map(|dir| dir.join(".cargo")).chain(cargo_home()) {
+            let path = vec![dir.join("config.toml"), dir.join("config")].into_iter().find(|path| path.is_file());
+            if let Some(path) = path {
+                let text = std::fs::read_to_string(&path)?;
+                let mut farther : Config = toml::from_str(&text).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, format!("unable to parse `{}`: {}", path.display(), err)))?;
+                farther.resolve_paths(dir.parent().unwrap_or(&dir));
+                config.merge(farther);
+            }
+        }
+        Ok(config)
+    }
+
+    /// `CARGO_BUILD_TARGET` or `build.target`
+    pub fn build_targets(&self) -> Vec<String> {
+        if let Ok(target) = std::env::var("CARGO_BUILD_TARGET") {
+            return vec![target];
+        }
+        match self.build.target.as_ref() {
+            None                            => Vec::new(),
+            Some(StringOrVec::String(t))    => vec![t.clone()],
+            Some(StringOrVec::Vec(t))       => t.clone(),
+        }
+    }
+
+    /// `CARGO_TARGET_{TRIPLE}_RUNNER` or `target.{triple}.runner`
+    pub fn runner(&self, triple: &str) -> Option<Vec<String>> {
+        if let Ok(runner) = std::env::var(runner_var(triple)) {
+            return Some(runner.split_whitespace().map(String::from).collect());
+        }
+        match self.target.get(triple)?.runner.as_ref()? {
+            StringOrVec::String(runner) => Some(runner.split_whitespace().map(String::from).collect()),
+            StringOrVec::Vec(runner)    => Some(runner.clone()),
+        }
+    }
+
+    /// `[env]` variables cargo would set, as `(name, value)`s: like cargo, only `force = true` ones override variables already in our environment.
+    pub fn env(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.env.iter().filter_map(|(name, value)| match value {
+            EnvValue::String(value)                 => Some((name.as_str(), value.as_str())).filter(|_| std::env::var_os(name).is_none()),
+            EnvValue::Table { value, force, .. }    => Some((name.as_str(), value.as_str())).filter(|_| *force || std::env::var_os(name).is_none()),
+        })
+    }
+
+    /// Resolve relative paths like `runner = "./scripts/run.sh"` relative to `root` (the parent of the `.cargo` directory), as cargo does.
+    fn resolve_paths(&mut self, root: &Path) {
+        for value in self.env.values_mut() {
+            if let EnvValue::Table { value, relative: true, .. } = value {
+                *value = root.join(&*value).display().to_string();
+            }
+        }
+        for target in self.target.values_mut() {
+            let mut runner = match target.runner.take() {
+                None                                => continue,
+                Some(StringOrVec::String(runner))   => runner.split_whitespace().map(String::from).collect(),
+                Some(StringOrVec::Vec(runner))      => runner,
+            };
+            if let Some(program) = runner.first_mut() {
+                if program.contains(['/', '\\']) && Path::new(program).is_relative() {
+                    *program = root.join(&*program).display().to_string();
+                }
+            }
+            target.runner = Some(StringOrVec::Vec(runner));
+        }
+    }
+
+    /// Fill in anything not already set from a `farther` config file.
+    fn merge(&mut self, farther: Config) {
+        if self.build.target.is_none() { self.build.target = farther.build.target; }
+        for (name, value) in farther.env {
+            self.env.entry(name).or_insert(value);
+        }
+        for (triple, farther) in farther.target {
+            let target = self.target.entry(triple).or_default();
+            if target.runner.is_none() { target.runner = farther.runner; }
+        }
+    }
+}
+
+/// `CARGO_TARGET_{TRIPLE}_RUNNER`
+pub(crate) fn runner_var(triple: &str) -> String {
+    format!("CARGO_TARGET_{}_RUNNER", triple.to_uppercase().replace(['-', '.'], "_"))
+}
+
+fn cargo_home() -> Option<PathBuf> {
+    if let Some(home) = std::env::var_os("CARGO_HOME") { return Some(PathBuf::from(home)) }
+    let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"))?;
+    Some(PathBuf::from(home).join(".cargo"))
+}
+
+
+
+#[cfg(test)] mod tests {
+    use super::*;
+
+    /// Parse a config file as if read from `{root}/.cargo/config.toml`.
+    fn parse(root: &str, text: &str) -> Config {
+        let mut config : Config = toml::from_str(text).unwrap();
+        config.resolve_paths(Path::new(root));
+        config
+    }
+
+    fn runner(config: &Config, triple: &str) -> Vec<String> {
+        match config.target[triple].runner.as_ref() {
+            Some(StringOrVec::Vec(runner))  => runner.clone(),
+            other                           => panic!("expected a resolved runner, got {:?}", other),
+        }
+    }
+
+    #[test] fn resolve_runners() {
+        let config = parse("/ws", r#"
+            [target.a-unknown-linux-gnu]
+            runner = "qemu-a -L /usr/a-linux-gnu"
+            [target.b-unknown-linux-gnu]
+            runner = "./scripts/run.sh --flag"
+            [target.c-unknown-linux-gnu]
+            runner = ["scripts/run.sh", "./not-the-program"]
+            [target.d-unknown-linux-gnu]
+            runner = "/usr/bin/run"
+        "#);
+        assert_eq!(runner(&config, "a-unknown-linux-gnu"), ["qemu-a", "-L", "/usr/a-linux-gnu"]);
+        assert_eq!(runner(&config, "b-unknown-linux-gnu"), [Path::new("/ws").join("./scripts/run.sh").display().to_string().as_str(), "--flag"]);
+        assert_eq!(runner(&config, "c-unknown-linux-gnu"), [Path::new("/ws").join("scripts/run.sh").display().to_string().as_str(), "./not-the-program"]);
+        assert_eq!(runner(&config, "d-unknown-linux-gnu"), ["/usr/bin/run"]);
+    }
+
+    #[test] fn resolve_env() {
+        let config = parse("/ws", r#"
+            [env]
+            PLAIN       = "assets"
+            ABSOLUTE    = { value = "assets" }
+            RELATIVE    = { value = "assets", relative = true }
+        "#);
+        assert_eq!(config.env().collect::<Vec<_>>(), [
+            ("ABSOLUTE", "assets"),
+            ("PLAIN",    "assets"),
+            ("RELATIVE", Path::new("/ws").join("assets").display().to_string().as_str()),
+        ]);
+    }
+
+    #[test] fn force_env() {
+        std::env::set_var("CARGO_VSC_TEST_FORCE_ENV_SET", "inherited");
+        let config = parse("/ws", r#"
+            [env]
+            CARGO_VSC_TEST_FORCE_ENV_SET        = "config"
+            CARGO_VSC_TEST_FORCE_ENV_UNSET      = "config"
+            CARGO_VSC_TEST_FORCE_ENV_FORCED     = { value = "config", force = true }
+        "#);
+        std::env::set_var("CARGO_VSC_TEST_FORCE_ENV_FORCED", "inherited");
+        assert_eq!(config.env().collect::<Vec<_>>(), [
+            ("CARGO_VSC_TEST_FORCE_ENV_FORCED", "config"),
+            ("CARGO_VSC_TEST_FORCE_ENV_UNSET",  "config"),
+        ]);
+    }
+
+    #[test] fn merge_nearer_first() {
+        let mut config = parse("/ws/pkg", r#"
+            [env]
+            NEAR = "pkg"
+            BOTH = { value = "pkg", relative = true }
+            [target.a-unknown-linux-gnu]
+            runner = "./near"
+        "#);
+        config.merge(parse("/ws", r#"
+            [build]
+            target = ["a-unknown-linux-gnu", "b-unknown-linux-gnu"]
+            [env]
+            FAR  = "ws"
+            BOTH = { value = "ws", relative = true }
+            [target.a-unknown-linux-gnu]
+            runner = "./far"
+            [target.b-unknown-linux-gnu]
+            runner = "./far"
+        "#));
+        config.merge(parse("/", r#"
+            [build]
+            target = "c-unknown-linux-gnu"
+        "#));
+
+        assert!(matches!(config.build.target.as_ref(), Some(StringOrVec::Vec(targets)) if targets == &["a-unknown-linux-gnu", "b-unknown-linux-gnu"]));
+        assert_eq!(config.env().collect::<Vec<_>>(), [
+            ("BOTH", Path::new("/ws/pkg").join("pkg").display().to_string().as_str()),
+            ("FAR",  "ws"),
+            ("NEAR", "pkg"),
+        ]);
+        assert_eq!(runner(&config, "a-unknown-linux-gnu"), [Path::new("/ws/pkg").join("./near").display().to_string()]);
+        assert_eq!(runner(&config, "b-unknown-linux-gnu"), [Path::new("/ws").join("./far").display().to_string()]);
+    }
+}
@@ -0,0 +1,100 @@
+use std::io;
+use std::path::Path;
+
+
+
+/// Read `NAME=value` pairs from a `.env` file.<br>
+/// Supports `# comments`, `export NAME=value`, and `"double"` / `'single'` quoted values, but not `${VAR}` expansion.
+pub(crate) fn read(path: &Path) -> io::Result<Vec<(String, String)>> {
+    parse(&std::fs::read_to_string(path)?, path)
+}
+
+/// Parse the contents of a `.env` file (`path` is only used for error messages.)
+fn parse(text: &str, path: &Path) -> io::Result<Vec<(String, String)>> {
+    let mut vars = Vec::new();
+    for (line_no, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') { continue }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let (name, value) = line.split_once('=').ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("{}:{}: expected `NAME=value`", path.display(), line_no+1)))?;
+        let value = value.trim();
+        let value = if let Some(value) = value.strip_prefix('"').and_then(double_quoted) {
+            value
+        } else if let Some((value, _comment)) = value.strip_prefix('\'').and_then(|v| v.split_once('\'')) {
+            value.into()
+        } else {
+            value.split(" #").next().unwrap_or_default().trim_end().into()
+        };
+        vars.push((name.trim().into(), value));
+    }
+    Ok(vars)
+}
+
+/// The unescaped contents of a `"double"` quoted value (after the opening `"`), ignoring anything (e.g. a `# comment`) after the closing `"`.
+fn double_quoted(value: &str) -> Option<String> {
+    let mut unquoted = String::new();
+    let mut chars = value.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"'     => return Some(unquoted),
+            '\\'    => match chars.next()? {
+                'n'     => unquoted.push('\n'),
+                ch @ ('"' | '\\') => unquoted.push(ch),
+                ch      => { unquoted.push('\\'); unquoted.push(ch) },
+            },
+            ch      => unquoted.push(ch),
+        }
+    }
+    None // unterminated
+}
+
+
+
+#[cfg(test)] mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Vec<(String, String)> {
+        super::parse(text, Path::new(".env")).unwrap()
+    }
+
+    fn vars(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    #[test] fn plain() {
+        assert_eq!(parse("A=1\n\n  B = two words  \nC=\n"), vars(&[("A", "1"), ("B", "two words"), ("C", "")]));
+    }
+
+    #[test] fn export() {
+        assert_eq!(parse("export A=1\nexport  B=2\nexported=3"), vars(&[("A", "1"), ("B", "2"), ("exported", "3")]));
+    }
+
+    #[test] fn comments() {
+        assert_eq!(parse("# A=1\n  # B=2\nC=3 # comment\nD=4#not a comment\nE=\"5 # not a comment\" # comment\nF='6 # not a comment' # comment"), vars(&[
+            ("C", "3"),
+            ("D", "4#not a comment"),
+            ("E", "5 # not a comment"),
+            ("F", "6 # not a comment"),
+        ]));
+    }
+
+    #[test] fn quotes() {
+        assert_eq!(parse(r#"A="a=b"
+B='single \n "quoted"'
+C="line\nbreak \"escaped\" back\\slash \t"
+D="unterminated
+E='unterminated
+F=it's"#), vars(&[
+            ("A", "a=b"),
+            ("B", r#"single \n "quoted""#),
+            ("C", "line\nbreak \"escaped\" back\\slash \\t"),
+            ("D", "\"unterminated"),
+            ("E", "'unterminated"),
+            ("F", "it's"),
+        ]));
+    }
+
+    #[test] fn invalid() {
+        assert!(super::parse("A=1\nB\n", Path::new(".env")).is_err());
+    }
+}
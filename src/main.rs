@@ -1,7 +1,12 @@
 #![forbid(unsafe_code)]
 
+mod artifacts;
+mod cargo_config;
+mod dotenv;
+mod manifest;
 mod metadata;
 mod run;
+mod rustc;
 
 fn main() {
     run::run()
@@ -0,0 +1,53 @@
+#![allow(dead_code)]
+
+use serde::*;
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+
+
+
+/// Cargo.toml<br>
+/// (only the parts cargo metadata doesn't expose)
+#[derive(Deserialize, Debug, Default)]
+pub(crate) struct Manifest {
+    #[serde(default)] pub profile: BTreeMap<String, Profile>,
+    // ...
+}
+
+/// Cargo.toml<br>
+///
+/// ```toml
+/// [profile.profiling]
+/// inherits = "release"
+/// debug = true
+/// ```
+#[derive(Deserialize, Debug, Default)]
+pub(crate) struct Profile {
+    pub inherits:   Option<String>,
+    pub debug:      Option<toml::Value>,
+    // ...
+}
+
+impl Profile {
+    /// `debug` is explicitly set to something other than `false`, `0`, or `"none"`.
+    pub fn has_debug_info(&self) -> bool {
+        match self.debug.as_ref() {
+            None                                => false,
+            Some(toml::Value::Boolean(b))       => *b,
+            Some(toml::Value::Integer(n))       => *n != 0,
+            Some(toml::Value::String(s))        => s != "none",
+            Some(_)                             => true,
+        }
+    }
+}
+
+
+
+impl Manifest {
+    pub fn read(path: &Path) -> io::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        toml::from_str(&text).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, format!("unable to parse `{}`: {}", path.display(), err)))
+    }
+}
@@ -2,7 +2,7 @@
 
 use serde::*;
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::io;
 use std::path::PathBuf;
 use std::process::{Command, Stdio};
@@ -17,8 +17,10 @@ pub(crate) type PackageId = String;
 #[derive(Deserialize, Debug)]
 pub(crate) struct Root {
     pub workspace_root: PathBuf,
+    pub target_directory: PathBuf,
     pub packages: Vec<PackageRef>,
     pub workspace_members: HashSet<PackageId>,
+    #[serde(default)] pub workspace_default_members: Vec<PackageId>, // cargo 1.71+
     pub metadata: Option<Metadata>,
     // ...
 }
@@ -31,6 +33,12 @@ pub(crate) struct PackageRef {
     pub id:             PackageId,
     pub name:           String,
     pub version:        String,
+    #[serde(default)] pub authors: Vec<String>,
+    pub description:    Option<String>,
+    pub license:        Option<String>,
+    pub license_file:   Option<PathBuf>,
+    pub rust_version:   Option<String>,
+    pub default_run:    Option<String>,
     pub repository:     Option<Url>, // present in cargo +1.47.0 metadata
     pub documentation:  Option<Url>, // MISSING in cargo +1.47.0 metadata, might lead to fewer tasks.json links in older cargo
     pub homepage:       Option<Url>, // MISSING in cargo +1.47.0 metadata, might lead to fewer tasks.json links in older cargo
@@ -46,10 +54,11 @@ pub(crate) struct PackageTarget {
     pub kind:           Vec<String>, // "lib", "example" (, "bin"?)
     pub crate_types:    Vec<String>, // "lib", "bin"
     pub name:           String,
+    #[serde(rename = "required-features")] #[serde(default)] pub required_features: Vec<String>,
     //pub src_path:       PathBuf,
     //pub edition:        String,
     //pub doctest:        bool,
-    //pub test:           bool,
+    pub test:           Option<bool>,
 }
 
 /// cargo metadata<br>
@@ -68,17 +77,168 @@ pub(crate) struct Metadata {
 ///
 /// [workspace.metadata.cargo-vsc]
 /// simple = true
+/// probe = true # build bins & examples via `cargo build --message-format=json` to find their real paths
+/// profiles = ["dev", "release", "profiling"] # defaults to dev, release, and any custom [profile.*]s
+/// release-debug = true # build "release" tasks with CARGO_PROFILE_RELEASE_DEBUG=true
+/// triples = ["x86_64-unknown-linux-musl"] # in addition to `build.target` (or the host)
+/// rr = true # generate `rr record` tasks and `rr replay` launch configurations (Linux)
+/// valgrind = true # generate `valgrind` memcheck tasks and vgdb launch configurations (Linux)
+/// sanitizers = ["address", "thread"] # generate nightly `-Zsanitizer=...` tasks and launch configurations (`thread` & `memory` also `-Zbuild-std`)
+/// hidden = ["release", "example"] # profiles and/or kinds of launch configurations to hide from the debug dropdown
+/// build-scripts = true # probe `cargo build -vv` for build scripts, and generate launch configurations to debug them
+///
+/// [workspace.metadata.cargo-vsc.prompt] # prompt for RUST_LOG, RUST_BACKTRACE, and args on launch
+/// rust-log = "debug"      # default selection
+/// rust-backtrace = "full" # default selection
+/// args = "--verbose"      # default input
+///
+/// [workspace.metadata.cargo-vsc.remote] # deploy bins over ssh & debug them there
+/// host = "user@lab-box"   # ssh destination
+/// path = "/tmp/deploy"    # remote directory to copy bins into
+/// triple = "aarch64-unknown-linux-gnu" # defaults to `build.target` (or the host), added to `triples` if missing
+/// gdbserver = 2345        # debug via `gdbserver :2345` instead of a remote gdb over ssh
+///
+/// [workspace.metadata.cargo-vsc.names] # placeholders: {package} {kind} {target} {profile} {triple} {variant}
+/// launch = "{target} • {kind} ({profile})" # launch configuration names (" ({variant})" is appended if omitted & nonempty)
+/// task = "build {target} ({profile})"      # build task labels (defaults to the command), also wrapped by e.g. "deploy (...)"
+///                                          # fixed tasks ("build", "test", ...) and compound builds keep their labels
+///
+/// [workspace.metadata.cargo-vsc.compound] # launch several bins at once
+/// demo = [{ bin = "server", args = ["--port", "8080"] }, { bin = "client", args = ["localhost:8080"] }]
+///
+/// [package.metadata.cargo-vsc]
+/// natvis = ["natvis/mytypes.natvis"] # relative to the package, replaces autodiscovered `*.natvis` files
+/// env-file = ".env.local" # relative to the package (or workspace), defaults to `.env` if it exists
+///
+/// [package.metadata.cargo-vsc.bin.server] # or [[...]] for multiple variants
+/// args = ["--port", "8080"]
+///
+/// [package.metadata.cargo-vsc.host] # debug this package's cdylib/dylib by launching:
+/// bin = "loader"              # a workspace bin (optionally with `package = "..."`), or:
+/// program = "C:/Program Files/Host/host.exe" # relative to the package
+/// args = ["--plugin", "{lib}"] # `{lib}` is replaced with the library's path
+/// env = { PLUGIN = "{lib}" }
 /// ```
 #[derive(Deserialize, Debug, Default)]
 pub(crate) struct MetadataCargoVsc {
-    pub simple: Option<bool>,
+    pub simple:     Option<bool>,
+    pub probe:      Option<bool>,
+    pub profiles:   Option<Vec<String>>,
+    #[serde(rename = "release-debug")] pub release_debug: Option<bool>,
+    pub triples:    Option<Vec<String>>,
+    pub natvis:     Option<Vec<PathBuf>>,
+    #[serde(rename = "env-file")] pub env_file: Option<PathBuf>,
+    pub prompt:     Option<MetadataPrompt>,
+    pub rr:         Option<bool>,
+    pub valgrind:   Option<bool>,
+    pub sanitizers: Option<Vec<String>>,
+    pub hidden:     Option<Vec<String>>,
+    #[serde(rename = "build-scripts")] pub build_scripts: Option<bool>,
+    #[serde(default)] pub names: MetadataNames,
+    pub host:       Option<MetadataHost>,
+    pub remote:     Option<MetadataRemote>,
+    #[serde(default)] pub compound: BTreeMap<String, Vec<MetadataCompoundMember>>,
+    #[serde(default)] pub bin:      BTreeMap<String, OneOrMany<MetadataLaunch>>,
+    #[serde(default)] pub example:  BTreeMap<String, OneOrMany<MetadataLaunch>>,
+}
+
+/// Cargo.toml<br>
+///
+/// ```toml
+/// [[package.metadata.cargo-vsc.bin.server]]
+/// name = "8080"           # defaults to `args`
+/// args = ["--port", "8080"]
+/// env = { RUST_LOG = "debug" }
+/// env-file = ".env"       # relative to the package
+/// cwd = "data"            # relative to the package
+/// stdin = "input.txt"     # relative to the package
+/// ```
+#[derive(Deserialize, Debug, Default, Clone)]
+pub(crate) struct MetadataLaunch {
+    pub name:       Option<String>,
+    #[serde(default)] pub args: Vec<String>,
+    #[serde(default)] pub env:  BTreeMap<String, String>,
+    #[serde(rename = "env-file")] pub env_file: Option<PathBuf>,
+    pub cwd:        Option<PathBuf>,
+    pub stdin:      Option<PathBuf>,
+}
+
+/// Cargo.toml<br>
+/// `[workspace.metadata.cargo-vsc.prompt]`
+#[derive(Deserialize, Debug, Default)]
+pub(crate) struct MetadataPrompt {
+    #[serde(rename = "rust-log")]       pub rust_log:       Option<String>,
+    #[serde(rename = "rust-backtrace")] pub rust_backtrace: Option<String>,
+    pub args: Option<String>,
 }
 
+/// Cargo.toml<br>
+/// `[workspace.metadata.cargo-vsc.remote]`
+#[derive(Deserialize, Debug)]
+pub(crate) struct MetadataRemote {
+    pub host:       String,
+    pub path:       String,
+    pub triple:     Option<String>,
+    pub gdbserver:  Option<u16>,
+}
 
+/// Cargo.toml<br>
+/// `[package.metadata.cargo-vsc.host]`
+#[derive(Deserialize, Debug)]
+pub(crate) struct MetadataHost {
+    pub package:    Option<String>,
+    pub bin:        Option<String>,
+    pub program:    Option<PathBuf>,
+    #[serde(default)] pub args: Vec<String>,
+    #[serde(default)] pub env:  BTreeMap<String, String>,
+}
+
+/// Cargo.toml<br>
+/// `[workspace.metadata.cargo-vsc.names]`
+#[derive(Deserialize, Debug, Default, Clone)]
+pub(crate) struct MetadataNames {
+    pub launch: Option<String>,
+    pub task:   Option<String>,
+}
+
+/// Cargo.toml<br>
+/// `[workspace.metadata.cargo-vsc.compound]`
+#[derive(Deserialize, Debug)]
+pub(crate) struct MetadataCompoundMember {
+    pub package:    Option<String>, // defaults to whichever workspace member has `bin`
+    pub bin:        String,
+    #[serde(default)] pub args: Vec<String>,
+}
+
+/// `{ ... }` or `[{ ... }, { ... }]`
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub(crate) enum OneOrMany<T> {
+    One(T),
+    Many(Vec<T>),
+}
+
+
+
+impl MetadataCargoVsc {
+    /// The launch configuration variants for a `kind` (`"bin"` or `"example"`) target named `name`.
+    pub fn launches(&self, kind: &str, name: &str) -> Vec<MetadataLaunch> {
+        let launches = match kind {
+            "bin"       => self.bin.get(name),
+            "example"   => self.example.get(name),
+            _other      => None,
+        };
+        match launches {
+            None                            => vec![MetadataLaunch::default()],
+            Some(OneOrMany::One(launch))    => vec![launch.clone()],
+            Some(OneOrMany::Many(launches)) => launches.clone(),
+        }
+    }
+}
 
 impl Root {
     pub fn get() -> io::Result<Self> {
-        let o = Command::new("cargo").args(&["metadata", "--all-features", "--format-version", "1"]).stderr(Stdio::inherit()).output()?;
+        let o = Command::new("cargo").args(["metadata", "--all-features", "--format-version", "1"]).stderr(Stdio::inherit()).output()?;
         match o.status.code() {
             Some(0) => {},
             Some(n) => return Err(io::Error::new(io::ErrorKind::BrokenPipe, format!("`cargo metadata` failed (exit code {})", n))),
@@ -1,7 +1,8 @@
 use crate::*;
 
-const AUTOGEN_JSON : &'static str = "// WARNING: autogenerated by cargo-vsc, may be overwritten if this comment remains!";
+const AUTOGEN_JSON : &str = "// WARNING: autogenerated by cargo-vsc, may be overwritten if this comment remains!";
 
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::{self, BufRead, BufReader, Write};
 use std::path::{Path, PathBuf};
@@ -10,8 +11,17 @@ use std::process::exit;
 pub fn run() {
     let meta = metadata::Root::get().unwrap_or_else(|err| { eprintln!("error parsing `cargo metadata`: {}", err); exit(1) });
     let vscode = create_vscode_dir(&meta).unwrap_or_else(|err| { eprintln!("error creating .vscode directory: {}", err); exit(1) });
-    let mut context = Context { meta, vscode, _non_exhaustive: () };
+    let sysroot = rustc::sysroot().map_err(|err| eprintln!("warning: unable to determine rustc sysroot, natvis files won't be included: {}", err)).ok();
+    let manifest = manifest::Manifest::read(&meta.workspace_root.join("Cargo.toml")).unwrap_or_else(|err| { eprintln!("warning: error reading workspace Cargo.toml: {}", err); Default::default() });
+    let cargo_config = cargo_config::Config::read(&std::env::current_dir().unwrap_or_else(|_| meta.workspace_root.clone())).unwrap_or_else(|err| { eprintln!("warning: error reading .cargo/config.toml: {}", err); Default::default() });
+    let rustc = rustc::version_verbose().map_err(|err| eprintln!("warning: unable to determine host target triple: {}", err)).ok();
+    let host = rustc.as_ref().map(|rustc| rustc.host.clone());
+    let profiles = Profile::list(&meta, &manifest);
+    let triples = Triple::list(&meta, &cargo_config, host.as_deref());
+    let rustc_commit = rustc.and_then(|rustc| rustc.commit_hash);
+    let mut context = Context { meta, vscode, sysroot, rustc_commit, cargo_config, host, profiles, triples, names: Default::default(), _non_exhaustive: () };
     context.meta.workspace_members.retain(|p| !p.starts_with("xtask "));
+    context.names = Names::new(&context);
 
     let mut errors = false;
     create_vscode_extensions_json   (&context).unwrap_or_else(|err| { eprintln!("error creating .vscode/extensions.json: {}", err); errors = true; });
@@ -33,13 +43,158 @@ fn create_vscode_dir(meta: &metadata::Root) -> io::Result<PathBuf> {
     }
 }
 
+/// Environment variables as `(name, value)`s, in order.
+type Env = Vec<(String, String)>;
+
 struct Context {
-    meta:   metadata::Root,
-    vscode: PathBuf,
+    meta:       metadata::Root,
+    vscode:     PathBuf,
+    sysroot:    Option<PathBuf>,
+    rustc_commit: Option<String>, // for mapping `/rustc/{commit-hash}/library/...` std sources
+    cargo_config: cargo_config::Config,
+    host:       Option<String>,
+    profiles:   Vec<Profile>,
+    triples:    Vec<Triple>,
+    names:      Names,
 
     _non_exhaustive: ()
 }
 
+/// A cargo profile to generate build tasks & launch configurations for.
+struct Profile {
+    name:           String,
+    force_debug:    bool, // build with `CARGO_PROFILE_{NAME}_DEBUG=true`
+}
+
+impl Profile {
+    /// `[workspace.metadata.cargo-vsc] profiles`, or `dev`, `release`, and any custom `[profile.*]`s in the workspace Cargo.toml.
+    fn list(meta: &metadata::Root, manifest: &manifest::Manifest) -> Vec<Self> {
+        let names = match meta.metadata.as_ref().and_then(|m| m.cargo_vsc.profiles.as_ref()) {
+            Some(profiles) => profiles.iter().map(|name| name.as_str()).collect(),
+            None => {
+                let mut names = vec!["dev", "release"];
+                names.extend(manifest.profile.keys().map(|name| name.as_str()).filter(|name| !["dev", "release", "test", "bench"].contains(name)));
+                names
+            },
+        };
+
+        let release_debug = meta.metadata.as_ref().and_then(|m| m.cargo_vsc.release_debug).unwrap_or(false);
+        let mut profiles = Vec::new();
+        for name in names {
+            let mut profile = Profile { name: name.into(), force_debug: false };
+            if name == "release" && !manifest.profile.get("release").is_some_and(|p| p.has_debug_info()) && std::env::var_os("CARGO_PROFILE_RELEASE_DEBUG").is_none() {
+                if release_debug {
+                    profile.force_debug = true;
+                } else {
+                    eprintln!("warning: `[profile.release]` has no debug info, \"release\" launch configurations will be difficult to debug");
+                    eprintln!("         set `debug = true` there, or `release-debug = true` in `[workspace.metadata.cargo-vsc]` to enable it for generated tasks only");
+                }
+            }
+            profiles.push(profile);
+        }
+        profiles
+    }
+
+    /// The `target/{dir}` this profile builds into, also used to label launch configurations.
+    fn dir(&self) -> &str {
+        match self.name.as_str() {
+            "dev" | "test"  => "debug",
+            "bench"         => "release",
+            other           => other,
+        }
+    }
+
+    /// Arguments to pass to `cargo build` to select this profile.
+    fn args(&self) -> Vec<&str> {
+        match self.name.as_str() {
+            "dev"       => vec![],
+            "release"   => vec!["--release"],
+            other       => vec!["--profile", other],
+        }
+    }
+
+    /// `(env, osx_env)`: `env` plus any variables to build with this profile, and extra variables for macOS.
+    fn env(&self, env: &[(String, String)]) -> (Env, Env) {
+        let mut env = env.to_vec();
+        let mut osx_env = Vec::new();
+        if self.force_debug {
+            let var = format!("CARGO_PROFILE_{}", self.name.to_uppercase().replace('-', "_"));
+            set_env(&mut env, &format!("{}_DEBUG", var), "true");
+            osx_env.push((format!("{}_SPLIT_DEBUGINFO", var), "packed".into())); // .dSYM
+        }
+        (env, osx_env)
+    }
+
+    /// `cargo_build` with this profile's and `triple`'s arguments appended.
+    fn cmd(&self, cargo_build: &str, triple: &Triple) -> String {
+        let mut cmd = cargo_build.to_string();
+        for arg in self.args().into_iter().chain(triple.args()) {
+            cmd.push(' ');
+            cmd.push_str(arg);
+        }
+        cmd
+    }
+}
+
+/// A `--target` triple to generate build tasks & launch configurations for.
+struct Triple {
+    name:       Option<String>, // None: cargo's default (the host)
+    runner:     Option<Vec<String>>,
+    native:     bool, // can run directly on the host
+}
+
+impl Triple {
+    /// `build.target` (or the host if unset), followed by any `[workspace.metadata.cargo-vsc] triples`, and the `remote.triple`.
+    fn list(meta: &metadata::Root, cargo_config: &cargo_config::Config, host: Option<&str>) -> Vec<Self> {
+        let mut names = cargo_config.build_targets().into_iter().map(Some).collect::<Vec<_>>();
+        if names.is_empty() { names.push(None) }
+        let cargo_vsc = meta.metadata.as_ref().map(|m| &m.cargo_vsc);
+        let remote_triple = cargo_vsc.and_then(|c| c.remote.as_ref()?.triple.as_ref());
+        for triple in cargo_vsc.and_then(|c| c.triples.as_ref()).into_iter().flatten().chain(remote_triple) {
+            if names.contains(&Some(triple.clone())) || (names.contains(&None) && host == Some(triple.as_str())) { continue }
+            names.push(Some(triple.clone()));
+        }
+
+        names.into_iter().map(|name| {
+            let runner = name.as_deref().or(host).and_then(|name| cargo_config.runner(name));
+            let native = match name.as_deref() {
+                None            => true,
+                Some(triple)    => host.is_some_and(|host| runs_on(host, triple)),
+            };
+            if !native && runner.is_none() && name.as_ref() != remote_triple { // remote launch configurations are still generated
+                eprintln!("note: `{}` binaries can't run on this host and have no `runner` configured, skipping their launch configurations", name.as_deref().unwrap_or_default());
+            }
+            Triple { name, runner, native }
+        }).collect()
+    }
+
+    /// Arguments to pass to `cargo build` to select this triple.
+    fn args(&self) -> Vec<&str> {
+        match self.name.as_deref() {
+            None            => vec![],
+            Some(triple)    => vec!["--target", triple],
+        }
+    }
+
+    /// The directory this triple's profiles build into.
+    fn dir(&self, meta: &metadata::Root) -> PathBuf {
+        match self.name.as_deref() {
+            None            => meta.target_directory.clone(),
+            Some(triple)    => meta.target_directory.join(triple),
+        }
+    }
+
+    fn launchable(&self) -> bool { self.native || self.runner.is_some() }
+}
+
+/// Can binaries built for `triple` run directly on `host`?  (A heuristic: same OS, and same or 32-bit x86 arch.)
+fn runs_on(host: &str, triple: &str) -> bool {
+    if host == triple { return true }
+    let arch    = |triple: &str| triple.split('-').next().unwrap_or_default().to_string();
+    let os      = |triple: &str| ["windows", "linux", "darwin", "freebsd", "netbsd", "openbsd"].iter().copied().find(|os| triple.split('-').any(|part| part == *os));
+    os(host).is_some() && os(host) == os(triple) && (arch(host) == arch(triple) || (arch(host) == "x86_64" && ["i686", "i586", "i386"].contains(&arch(triple).as_str())))
+}
+
 
 
 fn create_json(path: &Path) -> io::Result<File> {
@@ -62,7 +217,7 @@ fn create_vscode_extensions_json(Context { meta, vscode, .. }: &Context) -> io::
     writeln!(o, "{{")?;
     writeln!(o, "    \"recommendations\": [")?;
     write_ext(&mut o, "rust-lang.rust-analyzer")?;
-    if meta.packages.iter().any(|p| p.targets.iter().any(|t| t.kind.iter().any(|kind| ["example", "bin"].contains(&&**kind)))) {
+    if meta.packages.iter().any(|p| p.targets.iter().any(|t| t.kind.iter().any(|kind| ["example", "bin"].contains(&&**kind)) || test_harness(p, t).is_some())) {
         write_ext(&mut o, "ms-vscode.cpptools")?;
     }
     writeln!(o, "    ]")?;
@@ -77,16 +232,17 @@ fn write_ext(o: &mut impl io::Write, extension: &str) -> io::Result<()> {
 
 
 
-fn create_vscode_settings_json(Context { vscode, .. }: &Context) -> io::Result<()> {
-    let files_exclude = [
-        "target/*/*/*",     // clutters up search results
-    ];
+fn create_vscode_settings_json(Context { meta, vscode, .. }: &Context) -> io::Result<()> {
+    let mut files_exclude = Vec::new();
+    if let Ok(target) = meta.target_directory.strip_prefix(&meta.workspace_root) {
+        files_exclude.push(format!("{}/*/*/*", target.display()).replace('\\', "/")); // clutters up search results
+    }
 
     let path = vscode.join("settings.json");
     let mut o = create_json(&path)?;
     writeln!(o, "{{")?;
     writeln!(o, "    \"files.exclude\": {{")?;
-    for file_exclude in files_exclude.iter().copied() {
+    for file_exclude in files_exclude.iter() {
         writeln!(o, "        {}: true,", serde_json::to_string(file_exclude).unwrap())?;
     }
     writeln!(o, "    }}")?;
@@ -96,78 +252,1434 @@ fn create_vscode_settings_json(Context { vscode, .. }: &Context) -> io::Result<(
 
 
 
-fn create_vscode_launch_json(Context { meta, vscode, .. }: &Context) -> io::Result<()> {
+fn create_vscode_launch_json(Context { meta, vscode, sysroot, rustc_commit, cargo_config, host, profiles, triples, names, .. }: &Context) -> io::Result<()> {
     let path = vscode.join("launch.json");
     let mut o = create_json(&path)?;
     writeln!(o, "{{")?;
     writeln!(o, "    \"version\": \"0.2.0\",")?;
     writeln!(o, "    \"configurations\": [")?;
 
-    let member_packages = meta.packages.iter().filter(|package| meta.workspace_members.contains(&package.id));
-    let single_member_package = member_packages.clone().count() <= 1;
+    let mut member_packages = meta.packages.iter().filter(|package| meta.workspace_members.contains(&package.id)).collect::<Vec<_>>();
+    member_packages.sort_by_key(|package| (!is_default_member(meta, package), !package.targets.iter().any(|target| is_default_run(package, target)))); // stable: otherwise in `cargo metadata` order
+    let single_member_package = member_packages.len() <= 1;
+
+    let mut sysroot_debugger = Debugger::default();
+    if let Some(sysroot) = sysroot.as_ref() {
+        let rustlib = sysroot.join("lib").join("rustlib");
+        find_natvis(&rustlib.join("etc"), false, &mut sysroot_debugger.natvis)?;
+        sysroot_debugger.gdb_pretty_printers = Some(rustlib.join("etc").join("gdb_load_rust_pretty_printers.py")).filter(|path| path.is_file());
+        let src = rustlib.join("src").join("rust");
+        if let Some(commit) = rustc_commit.as_ref().filter(|_| src.is_dir()) {
+            sysroot_debugger.source_file_map.push((format!("/rustc/{}", commit), src.display().to_string()));
+        }
+    }
+
+    let harnesses = probe_harnesses(meta, member_packages.iter().copied(), None);
+
+    let probe = meta.metadata.as_ref().and_then(|m| m.cargo_vsc.probe).unwrap_or(false);
+    let probe_executables = |args: &[&str]| if !probe { Vec::new() } else { probe_artifacts(member_packages.iter().copied(), args).unwrap_or_else(|err| {
+        eprintln!("warning: unable to probe executable paths, guessing them instead: {}", err);
+        Vec::new()
+    })};
+    let builds = profiles.iter().flat_map(|profile| triples.iter().filter(|t| t.launchable()).map(move |triple| (profile, triple))).collect::<Vec<_>>();
+    let built = builds.iter().map(|(profile, triple)| probe_executables(&[&["build", "--bins", "--examples"][..], &profile.args(), &triple.args()].concat())).collect::<Vec<_>>();
+    let mut inputs = Vec::new();
+    let mut unique = Unique::default();
+    let prompt = meta.metadata.as_ref().and_then(|m| m.cargo_vsc.prompt.as_ref());
+    if let Some(prompt) = prompt {
+        let pick = |id: &str, description: &str, options: &[&str], default: &Option<String>, fallback: &str| {
+            let default = default.clone().unwrap_or_else(|| fallback.into());
+            let mut options = options.iter().map(|o| o.to_string()).collect::<Vec<_>>();
+            if !options.contains(&default) { options.push(default.clone()) }
+            Input { id: id.into(), description: description.into(), default: Some(default), options: Some(options) }
+        };
+        inputs.push(pick("RUST_LOG",        "RUST_LOG",         &["off", "error", "warn", "info", "debug", "trace"], &prompt.rust_log, "info"));
+        inputs.push(pick("RUST_BACKTRACE",  "RUST_BACKTRACE",   &["0", "1", "full"], &prompt.rust_backtrace, "1"));
+        inputs.push(Input { id: "args".into(), description: "program arguments".into(), default: Some(prompt.args.clone().unwrap_or_default()), options: None });
+    }
+    let rr = meta.metadata.as_ref().and_then(|m| m.cargo_vsc.rr).unwrap_or(false);
+    let valgrind = meta.metadata.as_ref().and_then(|m| m.cargo_vsc.valgrind).unwrap_or(false);
+    let sanitizers = meta.metadata.as_ref().and_then(|m| m.cargo_vsc.sanitizers.clone()).unwrap_or_default();
+    let remote = meta.metadata.as_ref().and_then(|m| m.cargo_vsc.remote.as_ref());
+    let hidden = meta.metadata.as_ref().and_then(|m| m.cargo_vsc.hidden.clone()).unwrap_or_default();
+    for sanitizer in sanitizers.iter().filter(|s| !SANITIZERS.contains(&s.as_str())) {
+        eprintln!("warning: unknown sanitizer {:?} in `[workspace.metadata.cargo-vsc] sanitizers` (expected one of {}), `-Zsanitizer={}` will likely fail", sanitizer, SANITIZERS.join(", "), sanitizer);
+    }
+    let harness_sanitizer_triple = triples[0].name.as_deref().or(host.as_deref()); // -Zsanitizer requires an explicit --target
+    let sanitized_harnesses = sanitizers.iter().map(|sanitizer| match harness_sanitizer_triple {
+        Some(triple)    => probe_harnesses(meta, member_packages.iter().copied(), Some((sanitizer, triple))),
+        None            => Vec::new(),
+    }).collect::<Vec<_>>();
+    let build_scripts = meta.metadata.as_ref().and_then(|m| m.cargo_vsc.build_scripts).unwrap_or(false);
+    let host_triple = Triple { name: None, runner: None, native: true }; // build scripts run on the host, without any runner
+    let mut kinds = ["bin", "example", "cdylib", "dylib", "lib", "test", "bench", "custom-build", "attach", "core dump", "rr replay", "valgrind", "remote"].iter().map(|k| k.to_string()).collect::<Vec<_>>();
+    kinds.extend(sanitizers.iter().map(|sanitizer| format!("{} sanitizer", sanitizer)));
+    let seq = std::cell::Cell::new(0);
+    // groups are contiguous: by package (default members first), kind (bins first), default-run first, and finally the order written
+    let presentation = |package: &metadata::PackageRef, target: Option<&metadata::PackageTarget>, kind: &str, profile: &str, config: &str| {
+        let package_rank = member_packages.iter().position(|p| p.id == package.id).unwrap_or_default() as u64;
+        let kind_rank = kinds.iter().position(|k| k == kind).unwrap_or(kinds.len()) as u64;
+        let target_rank = if target.is_some_and(|target| is_default_run(package, target)) { 0 } else { 1 };
+        seq.set(seq.get() + 1);
+        Presentation {
+            group:  if single_member_package { kind.into() } else { format!("{} • {}", package.name, kind) },
+            order:  ((package_rank * 100 + kind_rank) * 2 + target_rank) * 100_000 + seq.get(),
+            hidden: hidden.iter().any(|h| h == kind || h == profile || h == config),
+        }
+    };
+    let prompt_env = |env: &mut Env| if prompt.is_some() {
+        set_env(env, "RUST_LOG",        "${input:RUST_LOG}");
+        set_env(env, "RUST_BACKTRACE",  "${input:RUST_BACKTRACE}");
+    };
+    let inherited_env = member_packages.iter().map(|package| (package.id.as_str(), inherited_env_file_vars(meta, package))).collect::<BTreeMap<_, _>>();
+    // `"environment"` of `package`'s executables in `out_dir`, on top of its `"envFile"`
+    let launch_environment = |package: &metadata::PackageRef, out_dir: &Path| {
+        let mut env = inherited_env.get(package.id.as_str()).cloned().unwrap_or_default();
+        for (name, value) in cargo_run_environment(meta, cargo_config, package, out_dir) {
+            set_env(&mut env, &name, &value);
+        }
+        prompt_env(&mut env);
+        env
+    };
 
-    for package in member_packages {
+    for package in member_packages.iter().copied() {
         writeln!(o, "        // {}", package.name)?;
-        for target in package.targets.iter() {
+
+        let package_dir = package.manifest_path.parent().unwrap_or(&meta.workspace_root);
+        let debugger = package_debugger(meta, package, &sysroot_debugger)?;
+        let mut targets = package.targets.iter().collect::<Vec<_>>();
+        targets.sort_by_key(|target| !is_default_run(package, target)); // F5 defaults to the first configuration
+        for target in targets {
             for kind in target.kind.iter() {
-                let (subdir, cargo_build_debug) = match kind.as_str() {
-                    "example"   => ("examples/", format!("cargo build --package {} --example {}", package.name, target.name)),
-                    "bin"       => ("",          format!("cargo build --package {} --bin {}", package.name, target.name)),
-                    _other      => continue // not currently launchable
+                if kind == "custom-build" && build_scripts {
+                    for profile in profiles.iter() {
+                        for triple in triples.iter() {
+                            let config = profile.dir();
+                            let (mut env, osx_env) = profile.env(&[]); // same as the build task: the hashed build script paths depend on these
+                            if cfg!(target_os = "macos") { env.extend(osx_env) }
+                            set_env(&mut env, &build_override_debug_var(profile), "true");
+                            let script = match artifacts::BuildScript::probe(&build_script_args(package, profile, triple), &env) {
+                                Ok(scripts) => scripts.into_iter().find(|script| script.package_id == package.id),
+                                Err(err) => {
+                                    eprintln!("warning: unable to locate {:?}'s build script, skipping its launch configuration: {}", package.name, err);
+                                    None
+                                },
+                            };
+                            let script = match script {
+                                Some(script) => script,
+                                None => continue,
+                            };
+                            let program = workspace_path(meta, &script.executable);
+                            let vars = NameVars::new(package, kind, target, profile, triple);
+                            write_launch(&mut o, meta, &Launch {
+                                name:               unique.name(names.launch(single_member_package, &vars)),
+                                pre_launch_task:    names.task(&build_script_build(package, profile, triple), &vars),
+                                program:            program.clone(),
+                                windows_program:    program,
+                                cwd:                workspace_path(meta, package_dir),
+                                args:               Vec::new(),
+                                environment:        build_script_environment(meta, cargo_config, package, &script, profile, triple, host.as_deref()),
+                                env_file:           None, // cargo doesn't load `.env` files either
+                                triple:             &host_triple,
+                                debug_heap:         true,
+                                debugger:           &debugger,
+                                presentation:       presentation(package, Some(target), kind, &profile.name, config),
+                            })?;
+                        }
+                    }
+                }
+
+                if let Some(host) = library_host(package, target, kind) {
+                    let (program, windows_program) = match host_program(meta, package, host) {
+                        Some(program) => program,
+                        None => {
+                            eprintln!("warning: `[package.metadata.cargo-vsc.host]` of {:?} doesn't specify an existing workspace `bin` or a `program`, skipping its launch configurations", package.name);
+                            continue
+                        },
+                    };
+                    for (profile, triple) in builds.iter() {
+                        let config = profile.dir();
+                        let dir = triple.dir(meta).join(config);
+                        let lib = workspace_path(meta, &dir.join(dylib_filename(&target.name, triple.name.as_deref())));
+                        let (program, windows_program) = (program.replace("{dir}", &workspace_path(meta, &dir)), windows_program.replace("{dir}", &workspace_path(meta, &dir)));
+
+                        let mut args = host.args.iter().map(|arg| arg.replace("{lib}", &lib)).collect::<Vec<_>>();
+                        if prompt.is_some() {
+                            args.push("${input:args}".into());
+                        }
+                        let mut environment = launch_environment(package, &dir);
+                        for (name, value) in host.env.iter() {
+                            set_env(&mut environment, name, &value.replace("{lib}", &lib));
+                        }
+
+                        let vars = NameVars::new(package, kind, target, profile, triple);
+                        write_launch(&mut o, meta, &Launch {
+                            name:               unique.name(names.launch(single_member_package, &vars)),
+                            pre_launch_task:    names.task(&host_build(meta, package, host, profile, triple), &vars),
+                            program,
+                            windows_program,
+                            cwd:                "${workspaceFolder}".into(),
+                            args,
+                            environment,
+                            env_file:           env_file(meta, Some(package)).map(|env_file| workspace_path(meta, &env_file)),
+                            triple,
+                            debug_heap:         config == "debug",
+                            debugger:           &debugger,
+                            presentation:       presentation(package, Some(target), kind, &profile.name, config),
+                        })?;
+                    }
+                }
+
+                let (subdir, cargo_build) = match cargo_build(package, target, kind) {
+                    Some(build) => build,
+                    None        => continue, // launched via test_harness below, if at all
                 };
-                let cargo_build_release = format!("{} --release", cargo_build_debug);
-
-                for (config, build) in vec![("debug", cargo_build_debug), ("release", cargo_build_release)].into_iter() {
-                    let name = {
-                        let mut name = String::new();
-                        if !single_member_package {
-                            name.push_str(&package.name);
-                            name.push_str(" • ");
+
+                let variants = package.metadata.as_ref().map_or_else(|| vec![Default::default()], |m| m.cargo_vsc.launches(kind, &target.name));
+
+                for ((profile, triple), built) in builds.iter().zip(built.iter()) {
+                    let config = profile.dir();
+                    let (program, windows_program) = match find_executable(built, package, target, false) {
+                        Some((_, executable)) => (workspace_path(meta, executable), workspace_path(meta, executable)),
+                        None => (
+                            format!("{}/{}{}", workspace_path(meta, &triple.dir(meta).join(config)), subdir, target.name),
+                            format!("{}/{}{}.exe", workspace_path(meta, &triple.dir(meta).join(config)), subdir, target.name),
+                        ),
+                    };
+                    let vars = NameVars::new(package, kind, target, profile, triple);
+                    for variant in variants.iter() {
+                        let label = variant.name.clone().unwrap_or_else(|| variant.args.join(" "));
+                        let name = names.launch(single_member_package, &NameVars { variant: &label, ..vars });
+
+                        let mut args = variant.args.clone();
+                        if prompt.is_some() {
+                            args.push("${input:args}".into());
                         }
-                        if kind != "bin" {
-                            name.push_str(&kind);
-                            name.push_str(" • ");
+                        if let Some(stdin) = variant.stdin.as_ref() {
+                            args.push("<".into());
+                            args.push(workspace_path(meta, &package_dir.join(stdin)));
                         }
-                        if package.name != target.name {
-                            name.push_str(&target.name);
-                            name.push_str(" • ");
+
+                        let mut environment = launch_environment(package, &triple.dir(meta).join(config));
+                        for (name, value) in variant.env.iter() {
+                            set_env(&mut environment, name, value);
                         }
-                        name.push_str(&config);
-                        name
+
+                        write_launch(&mut o, meta, &Launch {
+                            name:               unique.name(name),
+                            pre_launch_task:    names.task(&profile.cmd(&cargo_build, triple), &vars),
+                            program:            program.clone(),
+                            windows_program:    windows_program.clone(),
+                            cwd:                variant.cwd.as_ref().map_or_else(|| "${workspaceFolder}".into(), |cwd| workspace_path(meta, &package_dir.join(cwd))),
+                            args,
+                            environment,
+                            env_file:           variant.env_file.as_ref().map(|env_file| package_dir.join(env_file)).or_else(|| env_file(meta, Some(package))).map(|env_file| workspace_path(meta, &env_file)),
+                            triple,
+                            debug_heap:         config == "debug",
+                            debugger:           &debugger,
+                            presentation:       presentation(package, Some(target), kind, &profile.name, config),
+                        })?;
+                    }
+                }
+
+                if kind != "bin" { continue }
+                if let Some((remote, triple)) = remote.and_then(|remote| Some((remote, remote_triple(remote, triples, host.as_deref())?))) {
+                    let mut debugger = debugger.clone();
+                    debugger.source_file_map.push((meta.workspace_root.display().to_string(), "${workspaceFolder}".into())); // paths as compiled
+                    for profile in profiles.iter() {
+                        let config = profile.dir();
+                        let program = triple.dir(meta).join(config).join(&target.name);
+                        let vars = NameVars::new(package, "remote", target, profile, triple);
+                        write_remote_launch(&mut o, &RemoteLaunch {
+                            name:               unique.name(names.launch(single_member_package, &vars)),
+                            pre_launch_task:    remote_deploy(meta, remote, &program, &names.task(&profile.cmd(&cargo_build, triple), &NameVars::new(package, kind, target, profile, triple))).0,
+                            program:            workspace_path(meta, &program),
+                            remote_program:     format!("{}/{}", remote.path.trim_end_matches('/'), target.name),
+                            remote,
+                            triple,
+                            debugger:           &debugger,
+                            presentation:       presentation(package, Some(target), "remote", &profile.name, config),
+                        })?;
+                    }
+                }
+                for ((profile, triple), built) in builds.iter().zip(built.iter()).filter(|((_, triple), _)| triple.native && triple.runner.is_none()) {
+                    let config = profile.dir();
+                    let (program, windows_program) = match find_executable(built, package, target, false) {
+                        Some((_, executable)) => (workspace_path(meta, executable), workspace_path(meta, executable)),
+                        None => (
+                            format!("{}/{}", workspace_path(meta, &triple.dir(meta).join(config)), target.name),
+                            format!("{}/{}.exe", workspace_path(meta, &triple.dir(meta).join(config)), target.name),
+                        ),
                     };
+                    for to in [AttachTo::Process, AttachTo::CoreDump, AttachTo::RrReplay, AttachTo::Valgrind].iter().copied() {
+                        if to == AttachTo::RrReplay && !rr { continue }
+                        if to == AttachTo::Valgrind && !valgrind { continue }
+                        if to == AttachTo::CoreDump && !inputs.iter().any(|i| i.id == "coreDump") {
+                            inputs.push(Input {
+                                id:             "coreDump".into(),
+                                description:    "path to the core dump / minidump to open".into(),
+                                default:        None,
+                                options:        None,
+                            });
+                        }
+                        let kind = match to { AttachTo::Process => "attach", AttachTo::CoreDump => "core dump", AttachTo::RrReplay => "rr replay", AttachTo::Valgrind => "valgrind" };
+                        let build_vars = NameVars::new(package, "bin", target, profile, triple);
+                        write_attach(&mut o, meta, &Attach {
+                            name:               unique.name(names.launch(single_member_package, &NameVars { kind, ..build_vars })),
+                            pre_launch_task:    Some(names.task(&profile.cmd(&cargo_build, triple), &build_vars)).filter(|_| to == AttachTo::Valgrind),
+                            program:            program.clone(),
+                            windows_program:    windows_program.clone(),
+                            to,
+                            debugger:           &debugger,
+                            presentation:       presentation(package, Some(target), kind, &profile.name, config),
+                        })?;
+                    }
 
-                    writeln!(o, "        {{")?;
-                    writeln!(o, "            \"name\":                     {},", serde_json::to_string(&name).unwrap())?;
-                    writeln!(o, "            \"type\":                     \"cppdbg\",")?;
-                    writeln!(o, "            \"request\":                  \"launch\",")?;
-                    writeln!(o, "            \"internalConsoleOptions\":   \"openOnSessionStart\",")?;
-                    writeln!(o, "            \"preLaunchTask\":            {},", serde_json::to_string(&build).unwrap())?;
-                    writeln!(o, "            \"program\":                  {},", serde_json::to_string(&format!("${{workspaceFolder}}/target/{}/{}{}", config, subdir, target.name)).unwrap())?;
-                    writeln!(o, "            \"cwd\":                      \"${{workspaceFolder}}\",")?;
-                    writeln!(o, "            \"environment\":              [ {{ \"name\": \"RUST_BACKTRACE\", \"value\": \"1\" }} ],")?;
-                    writeln!(o, "            \"windows\": {{")?;
-                    writeln!(o, "                \"type\":                 \"cppvsdbg\",")?; // despite vscode intellisense errors to the contrary, this totally works & is necessary
-                    writeln!(o, "                \"program\":              {},", serde_json::to_string(&format!("${{workspaceFolder}}/target/{}/{}{}.exe", config, subdir, target.name)).unwrap())?;
-                    writeln!(o, "                \"enableDebugHeap\":      {},", config == "debug")?;
-                    writeln!(o, "            }},")?;
-                    writeln!(o, "            \"symbolOptions\": {{")?; // despite vscode intellisense errors to the contrary, this totally works
-                    writeln!(o, "                // \"cachePath\":         \"${{env:TEMP}}/SymbolCache\"")?; // this is the platform default
-                    writeln!(o, "                \"searchMicrosoftSymbolServer\": true,")?;
-                    writeln!(o, "            }},")?;
-                    writeln!(o, "        }},")?;
+                    for sanitizer in sanitizers.iter() {
+                        let kind = format!("{} sanitizer", sanitizer);
+                        let sanitizer_triple = match triple.name.as_deref().or(host.as_deref()) {
+                            Some(triple)    => triple,
+                            None            => continue, // -Zsanitizer requires an explicit --target
+                        };
+                        let build = names.task(&profile.cmd(&cargo_build, triple), &NameVars::new(package, "bin", target, profile, triple));
+                        let (label, _, _) = sanitizer_cmd(meta, sanitizer, &profile.cmd(&cargo_build, triple), &build, sanitizer_triple);
+                        let dir = workspace_path(meta, &sanitizer_dir(meta, sanitizer).join(sanitizer_triple).join(config));
+                        let mut environment = launch_environment(package, &sanitizer_dir(meta, sanitizer).join(sanitizer_triple).join(config));
+                        if let Some(var) = sanitizer_options_var(sanitizer) {
+                            set_env(&mut environment, var, "abort_on_error=1"); // break into the debugger on the first report
+                        }
+                        write_launch(&mut o, meta, &Launch {
+                            name:               unique.name(names.launch(single_member_package, &NameVars::new(package, &kind, target, profile, triple))),
+                            pre_launch_task:    label,
+                            program:            format!("{}/{}", dir, target.name),
+                            windows_program:    format!("{}/{}.exe", dir, target.name),
+                            cwd:                "${workspaceFolder}".into(),
+                            args:               if prompt.is_some() { vec!["${input:args}".into()] } else { Vec::new() },
+                            environment,
+                            env_file:           env_file(meta, Some(package)).map(|env_file| workspace_path(meta, &env_file)),
+                            triple,
+                            debug_heap:         false, // conflicts with the sanitizer's own allocator
+                            debugger:           &debugger,
+                            presentation:       presentation(package, Some(target), &kind, &profile.name, config),
+                        })?;
+                    }
                 }
             }
+
+            if let Some((kind, build)) = test_harness(package, target) {
+                let config = if kind == "bench" { "release" } else { "debug" };
+                let (artifact, executable) = match find_executable(&harnesses, package, target, true) {
+                    Some((artifact, executable)) => (artifact, workspace_path(meta, executable)),
+                    None => continue, // not built by probe_harnesses (e.g. `test = false`, or the probe failed)
+                };
+
+                let environment = launch_environment(package, artifact.executable.as_deref().and_then(|e| e.parent()?.parent()).unwrap_or(&meta.target_directory));
+
+                let vars = NameVars::test_harness(package, kind, target, triples);
+                let mut launch = Launch {
+                    name:               unique.name(names.launch(single_member_package, &vars)),
+                    pre_launch_task:    names.task(&build, &vars),
+                    program:            executable.clone(),
+                    windows_program:    executable,
+                    cwd:                workspace_path(meta, package_dir), // like `cargo test`
+                    args:               Vec::new(),
+                    environment,
+                    env_file:           env_file(meta, Some(package)).map(|env_file| workspace_path(meta, &env_file)),
+                    triple:             &triples[0], // cargo's default
+                    debug_heap:         config == "debug",
+                    debugger:           &debugger,
+                    presentation:       presentation(package, Some(target), kind, if kind == "bench" { "bench" } else { "test" }, config),
+                };
+                write_launch(&mut o, meta, &launch)?;
+
+                if kind == "bench" { continue }
+                for (to, enabled) in [(AttachTo::RrReplay, rr), (AttachTo::Valgrind, valgrind)].iter().copied() {
+                    if !enabled { continue }
+                    let tool = if to == AttachTo::RrReplay { "rr replay" } else { "valgrind" };
+                    write_attach(&mut o, meta, &Attach {
+                        name:               unique.name(names.launch(single_member_package, &NameVars { kind: &format!("{} {}", tool, kind), ..NameVars::test_harness(package, kind, target, triples) })),
+                        pre_launch_task:    Some(launch.pre_launch_task.clone()).filter(|_| to == AttachTo::Valgrind),
+                        program:            launch.program.clone(),
+                        windows_program:    launch.windows_program.clone(),
+                        to,
+                        debugger:           &debugger,
+                        presentation:       presentation(package, Some(target), tool, "test", config),
+                    })?;
+                }
+                for (sanitizer, sanitized) in sanitizers.iter().zip(sanitized_harnesses.iter()) {
+                    let (triple, (artifact, executable)) = match harness_sanitizer_triple.zip(find_executable(sanitized, package, target, true)) {
+                        Some(found) => found,
+                        None        => continue, // probe failed
+                    };
+                    let tool = format!("{} sanitizer", sanitizer);
+                    let (label, _, _) = sanitizer_cmd(meta, sanitizer, &build, &launch.pre_launch_task, triple);
+                    let mut environment = launch_environment(package, artifact.executable.as_deref().and_then(|e| e.parent()?.parent()).unwrap_or(&meta.target_directory));
+                    if let Some(var) = sanitizer_options_var(sanitizer) {
+                        set_env(&mut environment, var, "abort_on_error=1"); // break into the debugger on the first report
+                    }
+                    write_launch(&mut o, meta, &Launch {
+                        name:               unique.name(names.launch(single_member_package, &NameVars { kind: &format!("{} {}", tool, kind), ..NameVars::test_harness(package, kind, target, triples) })),
+                        pre_launch_task:    label,
+                        program:            workspace_path(meta, executable),
+                        windows_program:    workspace_path(meta, executable),
+                        cwd:                workspace_path(meta, package_dir), // like `cargo test`
+                        args:               Vec::new(),
+                        environment,
+                        env_file:           launch.env_file.clone(),
+                        triple:             &triples[0],
+                        debug_heap:         false, // conflicts with the sanitizer's own allocator
+                        debugger:           &debugger,
+                        presentation:       presentation(package, Some(target), &tool, "test", config),
+                    })?;
+                }
+                launch.name = unique.name(names.launch(single_member_package, &NameVars { variant: "test…", ..vars }));
+                let test_input = match artifact.list_tests(package_dir) {
+                    Ok(tests) if tests.is_empty() => continue,
+                    Ok(tests) => {
+                        let input = Input {
+                            id:             format!("test-{}", inputs.iter().filter(|i| i.id.starts_with("test-")).count() + 1), // launch names may contain e.g. `:` or `}`
+                            description:    format!("test to debug ({})", launch.name),
+                            default:        None,
+                            options:        Some(tests),
+                        };
+                        let id = input.id.clone();
+                        inputs.push(input);
+                        id
+                    },
+                    Err(err) => {
+                        eprintln!("warning: unable to list tests for {:?}, prompting for a test name instead: {}", launch.name, err);
+                        if !inputs.iter().any(|i| i.id == "test") {
+                            inputs.push(Input {
+                                id:             "test".into(),
+                                description:    "name of the test to debug".into(),
+                                default:        None,
+                                options:        None,
+                            });
+                        }
+                        "test".into()
+                    },
+                };
+                launch.args = vec![format!("${{input:{}}}", test_input), "--exact".into(), "--nocapture".into()];
+                write_launch(&mut o, meta, &launch)?;
+            }
+        }
+    }
+
+    let compounds = meta.metadata.as_ref().map_or_else(Vec::new, |m| m.cargo_vsc.compound.iter().collect::<Vec<_>>());
+    let mut compound_configurations = Vec::new();
+    for (compound, members) in compounds.iter() {
+        writeln!(o, "        // {} (compound)", compound)?;
+        let triple = &triples[0]; // cargo's default
+        let mut configurations = Vec::new();
+        for member in members.iter() {
+            let (package, target) = match compound_member(meta, member) {
+                Some(found) => found,
+                None => {
+                    eprintln!("warning: compound {:?} member {:?} isn't a bin of any workspace member, skipping it", compound, member.bin);
+                    continue
+                },
+            };
+            let dir = triple.dir(meta).join("debug");
+            let mut args = member.args.clone();
+            if prompt.is_some() {
+                args.push("${input:args}".into());
+            }
+            let environment = launch_environment(package, &dir);
+            let name = unique.name(names.launch(single_member_package, &NameVars { package, kind: "compound", target, profile: "dev", config: "debug", triple: triple.name.as_deref(), variant: compound }));
+            seq.set(seq.get() + 1);
+            write_launch(&mut o, meta, &Launch {
+                name:               name.clone(),
+                pre_launch_task:    String::new(), // built by the compound's preLaunchTask
+                program:            format!("{}/{}", workspace_path(meta, &dir), target.name),
+                windows_program:    format!("{}/{}.exe", workspace_path(meta, &dir), target.name),
+                cwd:                "${workspaceFolder}".into(),
+                args,
+                environment,
+                env_file:           env_file(meta, Some(package)).map(|env_file| workspace_path(meta, &env_file)),
+                triple,
+                debug_heap:         true,
+                debugger:           &package_debugger(meta, package, &sysroot_debugger)?,
+                presentation:       Presentation { group: "compounds".into(), order: COMPOUND_ORDER + seq.get(), hidden: false },
+            })?;
+            configurations.push(name);
         }
+        compound_configurations.push(configurations);
     }
 
-    writeln!(o, "    ]")?; // configurations
+    writeln!(o, "    ],")?; // configurations
+    if !compounds.is_empty() {
+        writeln!(o, "    \"compounds\": [")?;
+        for ((compound, _), configurations) in compounds.iter().zip(compound_configurations.iter()) {
+            let configurations = configurations.iter().map(|name| serde_json::to_string(name).unwrap()).collect::<Vec<_>>();
+            writeln!(o, "        {{")?;
+            writeln!(o, "            \"name\":                     {},", serde_json::to_string(compound).unwrap())?;
+            writeln!(o, "            \"configurations\":           [ {} ],", configurations.join(", "))?;
+            writeln!(o, "            \"preLaunchTask\":            {},", serde_json::to_string(&compound_build_label(compound)).unwrap())?;
+            writeln!(o, "            \"stopAll\":                  true,")?;
+            write_presentation(&mut o, &Presentation { group: "compounds".into(), order: COMPOUND_ORDER, hidden: false })?;
+            writeln!(o, "        }},")?;
+        }
+        writeln!(o, "    ],")?; // compounds
+    }
+    writeln!(o, "    \"inputs\": [")?;
+    for input in inputs.iter() {
+        write_input(&mut o, input)?;
+    }
+    writeln!(o, "    ]")?; // inputs
     writeln!(o, "}}")?;
     Ok(())
 }
 
+/// A single launch.json `"configurations"` entry.
+struct Launch<'a> {
+    name:               String,
+    pre_launch_task:    String, // "": none
+    program:            String,
+    windows_program:    String,
+    cwd:                String,
+    args:               Vec<String>,
+    environment:        Env,
+    env_file:           Option<String>,
+    triple:             &'a Triple, // launched via `triple.runner`, if any
+    debug_heap:         bool,
+    debugger:           &'a Debugger,
+    presentation:       Presentation,
+}
+
+/// A launch.json entry for debugging an already running (or crashed, or recorded) `program`.
+struct Attach<'a> {
+    name:               String,
+    pre_launch_task:    Option<String>, // AttachTo::Valgrind only
+    program:            String,
+    windows_program:    String,
+    to:                 AttachTo,
+    debugger:           &'a Debugger,
+    presentation:       Presentation,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum AttachTo {
+    Process,    // `"request": "attach"` to a picked process
+    CoreDump,   // `"request": "launch"` with a prompted `"coreDumpPath"` / `"dumpPath"`
+    RrReplay,   // `"request": "launch"` against `rr replay -s {RR_PORT}` of the latest `rr record`ed trace
+    Valgrind,   // `"request": "launch"` of `program` under valgrind's gdbserver, via vgdb
+}
+
+/// A launch.json entry for debugging a bin deployed to `[workspace.metadata.cargo-vsc.remote]`.
+struct RemoteLaunch<'a> {
+    name:               String,
+    pre_launch_task:    String,
+    program:            String, // local
+    remote_program:     String,
+    remote:             &'a metadata::MetadataRemote,
+    triple:             &'a Triple,
+    debugger:           &'a Debugger,
+    presentation:       Presentation,
+}
+
+/// launch.json `"presentation"`: how a configuration is grouped, sorted, and/or hidden in the debug dropdown.
+struct Presentation {
+    group:              String,
+    order:              u64,
+    hidden:             bool,
+}
+
+/// `Presentation::order` of compounds, after every other configuration.
+/// VS Code parses `order` as a JS number: keep `COMPOUND_ORDER + seq` well below 2^53, lest it round.
+const COMPOUND_ORDER : u64 = 1 << 40;
+
+/// Debugger setup shared by every launch.json configuration of a package.
+#[derive(Clone, Default)]
+struct Debugger {
+    natvis:                 Vec<PathBuf>,
+    gdb_pretty_printers:    Option<PathBuf>,            // {sysroot}/lib/rustlib/etc/gdb_load_rust_pretty_printers.py
+    source_file_map:        Vec<(String, String)>,      // e.g. ("/rustc/{commit-hash}", "{sysroot}/lib/rustlib/src/rust")
+}
+
+/// `RUST_BACKTRACE=1`, plus the environment `cargo run` / `cargo test` would provide `package`'s executables in `out_dir`.
+fn cargo_run_environment(meta: &metadata::Root, cargo_config: &cargo_config::Config, package: &metadata::PackageRef, out_dir: &Path) -> Env {
+    let mut env = vec![("RUST_BACKTRACE".to_string(), "1".to_string())];
+    let package_dir = package.manifest_path.parent().unwrap_or(&meta.workspace_root);
+    let version = package.version.split('+').next().unwrap_or_default();
+    let (version_core, version_pre) = version.split_once('-').unwrap_or((version, ""));
+    let mut version_core = version_core.split('.');
+
+    for (name, value) in cargo_config.env() {
+        set_env(&mut env, name, value);
+    }
+    if let Ok(cargo) = std::env::var("CARGO") {
+        set_env(&mut env, "CARGO", &cargo);
+    }
+    set_env(&mut env, "CARGO_MANIFEST_DIR",         &workspace_path(meta, package_dir));
+    set_env(&mut env, "CARGO_MANIFEST_PATH",        &workspace_path(meta, &package.manifest_path));
+    set_env(&mut env, "CARGO_PKG_NAME",             &package.name);
+    set_env(&mut env, "CARGO_PKG_VERSION",          &package.version);
+    set_env(&mut env, "CARGO_PKG_VERSION_MAJOR",    version_core.next().unwrap_or_default());
+    set_env(&mut env, "CARGO_PKG_VERSION_MINOR",    version_core.next().unwrap_or_default());
+    set_env(&mut env, "CARGO_PKG_VERSION_PATCH",    version_core.next().unwrap_or_default());
+    set_env(&mut env, "CARGO_PKG_VERSION_PRE",      version_pre);
+    set_env(&mut env, "CARGO_PKG_AUTHORS",          &package.authors.join(":"));
+    set_env(&mut env, "CARGO_PKG_DESCRIPTION",      package.description .as_deref().unwrap_or_default());
+    set_env(&mut env, "CARGO_PKG_HOMEPAGE",         package.homepage    .as_deref().unwrap_or_default());
+    set_env(&mut env, "CARGO_PKG_REPOSITORY",       package.repository  .as_deref().unwrap_or_default());
+    set_env(&mut env, "CARGO_PKG_LICENSE",          package.license     .as_deref().unwrap_or_default());
+    set_env(&mut env, "CARGO_PKG_LICENSE_FILE",     &package.license_file.as_ref().map_or_else(String::new, |f| f.display().to_string()));
+    set_env(&mut env, "CARGO_PKG_RUST_VERSION",     package.rust_version.as_deref().unwrap_or_default());
+
+    let lib_dirs = [out_dir.join("deps"), out_dir.to_path_buf()];
+    let lib_dirs = lib_dirs.iter().map(|dir| workspace_path(meta, dir));
+    let (var, sep) = if cfg!(windows) { ("PATH", ";") } else if cfg!(target_os = "macos") { ("DYLD_FALLBACK_LIBRARY_PATH", ":") } else { ("LD_LIBRARY_PATH", ":") };
+    let inherited = std::env::var_os(var).filter(|path| !path.is_empty()).map(|_| format!("${{env:{}}}", var)); // an empty entry would search the cwd
+    let lib_path = lib_dirs.chain(inherited).collect::<Vec<_>>().join(sep);
+    set_env(&mut env, var, &lib_path);
+    env
+}
+
+/// The environment cargo runs `package`'s build `script` with: as logged by `cargo build -vv` if it ran, or else reconstructed.
+fn build_script_environment(meta: &metadata::Root, cargo_config: &cargo_config::Config, package: &metadata::PackageRef, script: &artifacts::BuildScript, profile: &Profile, triple: &Triple, host: Option<&str>) -> Env {
+    let mut env = vec![("RUST_BACKTRACE".to_string(), "1".to_string())];
+    if let Some(logged) = script.env.as_ref() {
+        for (name, value) in logged.iter() {
+            let value = if Path::new(value).is_absolute() { workspace_path(meta, Path::new(value)) } else { value.clone() };
+            set_env(&mut env, name, &value);
+        }
+        return env;
+    }
+
+    let out_dir = meta.target_directory.join(profile.dir()); // build scripts are built for the host
+    env = cargo_run_environment(meta, cargo_config, package, &out_dir);
+    let target = triple.name.as_deref().or(host).unwrap_or_default();
+    match rustc::cfg(triple.name.as_deref()) {
+        Ok(cfgs) => {
+            let mut names = cfgs.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>();
+            names.dedup();
+            for name in names {
+                let values = cfgs.iter().filter(|(n, _)| n == name).filter_map(|(_, value)| value.as_deref()).collect::<Vec<_>>();
+                set_env(&mut env, &format!("CARGO_CFG_{}", name.to_uppercase()), &values.join(","));
+            }
+        },
+        Err(err) => eprintln!("warning: unable to determine `{}`'s cfgs, {:?}'s build script won't have `CARGO_CFG_*` variables: {}", target, package.name, err),
+    }
+    for feature in script.features.iter() {
+        set_env(&mut env, &format!("CARGO_FEATURE_{}", feature.to_uppercase().replace('-', "_")), "1");
+    }
+    if let Some(out_dir) = script.out_dir.as_ref() {
+        set_env(&mut env, "OUT_DIR", &workspace_path(meta, out_dir));
+    }
+    set_env(&mut env, "TARGET", target);
+    set_env(&mut env, "HOST", host.unwrap_or_default());
+    set_env(&mut env, "NUM_JOBS", &std::thread::available_parallelism().map_or(1, |n| n.get()).to_string());
+    if let Some(artifact) = script.profile.as_ref() {
+        set_env(&mut env, "OPT_LEVEL", &artifact.opt_level);
+        set_env(&mut env, "DEBUG", if artifact.debuginfo == 0 || artifact.debuginfo == "none" { "false" } else { "true" });
+        set_env(&mut env, "PROFILE", if artifact.opt_level == "0" { "debug" } else { "release" });
+    }
+    set_env(&mut env, "RUSTC", &std::env::var("RUSTC").unwrap_or_else(|_| "rustc".into()));
+    env
+}
+
+/// The `.env` file for `package` (or the workspace if `None`):
+/// `env-file` from `[package.metadata.cargo-vsc]`, `{package}/.env`, `env-file` from `[workspace.metadata.cargo-vsc]`, or `{workspace}/.env`.
+fn env_file(meta: &metadata::Root, package: Option<&metadata::PackageRef>) -> Option<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(package) = package {
+        dirs.push((package.manifest_path.parent().unwrap_or(&meta.workspace_root), package.metadata.as_ref()));
+    }
+    dirs.push((meta.workspace_root.as_path(), meta.metadata.as_ref()));
+
+    for (dir, metadata) in dirs {
+        if let Some(env_file) = metadata.and_then(|m| m.cargo_vsc.env_file.as_ref()) {
+            return Some(dir.join(env_file));
+        }
+        let env_file = dir.join(".env");
+        if env_file.is_file() {
+            return Some(env_file);
+        }
+    }
+    None
+}
+
+/// The variables of the workspace's `env_file` that `package`'s own `env_file` doesn't override.<br>
+/// Tasks merge both files, but launch configurations' `"envFile"` only takes one: the rest goes in `"environment"`.
+fn inherited_env_file_vars(meta: &metadata::Root, package: &metadata::PackageRef) -> Env {
+    let (workspace, package) = match (env_file(meta, None), env_file(meta, Some(package))) {
+        (Some(workspace), Some(package)) if workspace != package => (workspace, package),
+        _ => return Vec::new(), // nothing to merge
+    };
+    let overridden = dotenv::read(&package).unwrap_or_default(); // unreadable files are warned about by `read_env_file`
+    dotenv::read(&workspace).unwrap_or_default().into_iter().filter(|(name, _)| !overridden.iter().any(|(n, _)| n == name)).collect()
+}
+
+fn read_env_file(path: &Path) -> Env {
+    dotenv::read(path).unwrap_or_else(|err| {
+        eprintln!("warning: unable to read `{}`, tasks won't use it: {}", path.display(), err);
+        Vec::new()
+    })
+}
+
+fn set_env(env: &mut Env, name: &str, value: &str) {
+    match env.iter_mut().find(|(n, _)| n == name) {
+        Some((_, v))    => *v = value.into(),
+        None            => env.push((name.into(), value.into())),
+    }
+}
+
+/// Port `qemu-* -g {port}` and other gdbservers are told to listen on.
+const GDBSERVER_PORT : u16 = 1234;
+
+/// Port `rr replay -s {port}` is told to listen on.
+const RR_PORT : u16 = 50505;
+
+fn write_launch(o: &mut impl io::Write, meta: &metadata::Root, launch: &Launch) -> io::Result<()> {
+    writeln!(*o, "        {{")?;
+    writeln!(*o, "            \"name\":                     {},", serde_json::to_string(&launch.name).unwrap())?;
+    writeln!(*o, "            \"type\":                     \"cppdbg\",")?;
+    writeln!(*o, "            \"request\":                  \"launch\",")?;
+    writeln!(*o, "            \"internalConsoleOptions\":   \"openOnSessionStart\",")?;
+    write_presentation(o, &launch.presentation)?;
+    if !launch.pre_launch_task.is_empty() {
+        writeln!(*o, "            \"preLaunchTask\":            {},", serde_json::to_string(&launch.pre_launch_task).unwrap())?;
+    }
+    let mut program = launch.program.clone();
+    let mut windows_program = launch.windows_program.clone();
+    let mut args = launch.args.clone();
+    let mut setup_commands = Vec::new();
+    match launch.triple.runner.as_deref() {
+        None | Some([]) => {},
+        Some([runner, runner_args @ ..]) if Path::new(runner).file_name().is_some_and(|name| name.to_string_lossy().starts_with("qemu-")) => {
+            // qemu-user can't be debugged through, but has a built-in gdbserver
+            let mut server_args = runner_args.to_vec();
+            server_args.extend(vec!["-g".into(), GDBSERVER_PORT.to_string(), program.clone()]);
+            server_args.append(&mut args);
+            let server_args = server_args.iter().map(|arg| quote_server_arg(arg)).collect::<Vec<_>>();
+            writeln!(*o, "            \"MIMode\":                   \"gdb\",")?;
+            if !launch.triple.native {
+                writeln!(*o, "            \"miDebuggerPath\":           \"gdb-multiarch\",")?;
+            }
+            writeln!(*o, "            \"miDebuggerServerAddress\":  \"localhost:{}\",", GDBSERVER_PORT)?;
+            writeln!(*o, "            \"debugServerPath\":          {},", serde_json::to_string(&workspace_path(meta, Path::new(runner))).unwrap())?;
+            writeln!(*o, "            \"debugServerArgs\":          {},", serde_json::to_string(&server_args.join(" ")).unwrap())?;
+            // qemu prints nothing once listening, so there's no `"serverStarted"` to wait for: have gdb retry connecting instead
+            setup_commands.push("set tcp auto-retry on".to_string());
+            setup_commands.push("set tcp connect-timeout 15".to_string());
+        },
+        Some([runner, runner_args @ ..]) if launch.triple.native => {
+            // like `cargo run`: `{runner} {runner_args} {program} {args}`, but debugging `program` once the runner `exec`s it
+            let wrapper = std::iter::once(workspace_path(meta, Path::new(runner))).chain(runner_args.iter().cloned()).map(|arg| quote_sh_arg(&arg)).collect::<Vec<_>>();
+            writeln!(*o, "            \"MIMode\":                   \"gdb\",")?;
+            setup_commands.push(format!("set exec-wrapper {}", wrapper.join(" ")));
+        },
+        Some([runner, runner_args @ ..]) => {
+            // `program` can't run on this host without the runner (e.g. an emulator without a gdbserver): debug the runner itself
+            let mut runner_args = runner_args.to_vec();
+            runner_args.push(program);
+            runner_args.append(&mut args);
+            program = workspace_path(meta, Path::new(runner));
+            windows_program = program.clone();
+            args = runner_args;
+        },
+    }
+    writeln!(*o, "            \"program\":                  {},", serde_json::to_string(&program).unwrap())?;
+    writeln!(*o, "            \"cwd\":                      {},", serde_json::to_string(&launch.cwd).unwrap())?;
+    if !args.is_empty() {
+        let args = args.iter().map(|arg| serde_json::to_string(arg).unwrap()).collect::<Vec<_>>();
+        writeln!(*o, "            \"args\":                     [ {} ],", args.join(", "))?;
+    }
+    let environment = launch.environment.iter().map(|(name, value)| format!("{{ \"name\": {}, \"value\": {} }}", serde_json::to_string(name).unwrap(), serde_json::to_string(value).unwrap())).collect::<Vec<_>>();
+    if environment.len() <= 1 {
+        writeln!(*o, "            \"environment\":              [ {} ],", environment.join(""))?;
+    } else {
+        writeln!(*o, "            \"environment\":              [")?;
+        for var in environment.iter() {
+            writeln!(*o, "                {},", var)?;
+        }
+        writeln!(*o, "            ],")?;
+    }
+    if let Some(env_file) = launch.env_file.as_ref() {
+        writeln!(*o, "            \"envFile\":                  {},", serde_json::to_string(env_file).unwrap())?;
+    }
+    writeln!(*o, "            \"windows\": {{")?;
+    writeln!(*o, "                \"type\":                 \"cppvsdbg\",")?; // despite vscode intellisense errors to the contrary, this totally works & is necessary
+    writeln!(*o, "                \"program\":              {},", serde_json::to_string(&windows_program).unwrap())?;
+    writeln!(*o, "                \"enableDebugHeap\":      {},", launch.debug_heap)?;
+    write_visualizer_files(o, meta, launch.debugger)?;
+    writeln!(*o, "            }},")?;
+    write_debugger_setup(o, launch.debugger, &setup_commands)?;
+    writeln!(*o, "        }},")?;
+    Ok(())
+}
+
+/// Quote `arg` for `"debugServerArgs"`, which is split like a Windows command line (even on Linux.)
+fn quote_server_arg(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains([' ', '\t', '"']) && !arg.contains("${") { return arg.into() } // `${workspaceFolder}` may expand to spaces
+    let mut quoted = String::from("\"");
+    let mut backslashes = 0;
+    for ch in arg.chars() {
+        match ch {
+            '\\'  => backslashes += 1,
+            '"'     => { quoted.extend(std::iter::repeat_n('\\', backslashes * 2 + 1)); backslashes = 0 },
+            _       => { quoted.extend(std::iter::repeat_n('\\', backslashes)); backslashes = 0 },
+        }
+        if ch != '\\' { quoted.push(ch) }
+    }
+    quoted.extend(std::iter::repeat_n('\\', backslashes * 2));
+    quoted.push('"');
+    quoted
+}
+
+/// Quote `arg` for gdb's `set exec-wrapper`, which is run by `/bin/sh`.
+fn quote_sh_arg(arg: &str) -> String {
+    if !arg.is_empty() && arg.chars().all(|ch| ch.is_ascii_alphanumeric() || "-_./:=,+@%".contains(ch)) { return arg.into() }
+    format!("'{}'", arg.replace('\'', "'\\''"))
+}
+
+fn write_attach(o: &mut impl io::Write, meta: &metadata::Root, attach: &Attach) -> io::Result<()> {
+    writeln!(*o, "        {{")?;
+    writeln!(*o, "            \"name\":                     {},", serde_json::to_string(&attach.name).unwrap())?;
+    writeln!(*o, "            \"type\":                     \"cppdbg\",")?;
+    write_presentation(o, &attach.presentation)?;
+    match attach.to {
+        AttachTo::Process => {
+            writeln!(*o, "            \"request\":                  \"attach\",")?;
+            writeln!(*o, "            \"program\":                  {},", serde_json::to_string(&attach.program).unwrap())?; // for symbols
+            writeln!(*o, "            \"processId\":                \"${{command:pickProcess}}\",")?;
+            writeln!(*o, "            \"windows\": {{")?;
+            writeln!(*o, "                \"type\":                 \"cppvsdbg\",")?;
+        },
+        AttachTo::CoreDump => {
+            writeln!(*o, "            \"request\":                  \"launch\",")?;
+            writeln!(*o, "            \"program\":                  {},", serde_json::to_string(&attach.program).unwrap())?;
+            writeln!(*o, "            \"coreDumpPath\":             \"${{input:coreDump}}\",")?;
+            writeln!(*o, "            \"cwd\":                      \"${{workspaceFolder}}\",")?;
+            writeln!(*o, "            \"windows\": {{")?;
+            writeln!(*o, "                \"type\":                 \"cppvsdbg\",")?;
+            writeln!(*o, "                \"program\":              {},", serde_json::to_string(&attach.windows_program).unwrap())?;
+            writeln!(*o, "                \"dumpPath\":             \"${{input:coreDump}}\",")?;
+        },
+        AttachTo::RrReplay => {
+            writeln!(*o, "            \"request\":                  \"launch\",")?;
+            writeln!(*o, "            \"program\":                  {},", serde_json::to_string(&attach.program).unwrap())?;
+            writeln!(*o, "            \"cwd\":                      \"${{workspaceFolder}}\",")?;
+            writeln!(*o, "            \"MIMode\":                   \"gdb\",")?;
+            writeln!(*o, "            \"miDebuggerServerAddress\":  \"localhost:{}\",", RR_PORT)?;
+            writeln!(*o, "            \"debugServerPath\":          \"rr\",")?;
+            writeln!(*o, "            \"debugServerArgs\":          \"replay -s {}\",", RR_PORT)?;
+            writeln!(*o, "            \"serverStarted\":            \"target extended-remote\",")?; // rr prints the gdb command line to connect with once listening
+            write_debugger_setup(o, attach.debugger, &[])?;
+            writeln!(*o, "        }},")?;
+            return Ok(()) // no windows support
+        },
+        AttachTo::Valgrind => {
+            writeln!(*o, "            \"request\":                  \"launch\",")?;
+            if let Some(pre_launch_task) = attach.pre_launch_task.as_ref() {
+                writeln!(*o, "            \"preLaunchTask\":            {},", serde_json::to_string(pre_launch_task).unwrap())?;
+            }
+            writeln!(*o, "            \"program\":                  {},", serde_json::to_string(&attach.program).unwrap())?;
+            writeln!(*o, "            \"cwd\":                      \"${{workspaceFolder}}\",")?;
+            writeln!(*o, "            \"MIMode\":                   \"gdb\",")?;
+            writeln!(*o, "            \"miDebuggerServerAddress\":  \"| vgdb\",")?;
+            writeln!(*o, "            \"debugServerPath\":          \"valgrind\",")?;
+            writeln!(*o, "            \"debugServerArgs\":          {},", serde_json::to_string(&format!("--vgdb=yes --vgdb-error=0 {}", attach.program)).unwrap())?; // then stops on every error once gdb is attached
+            writeln!(*o, "            \"serverStarted\":            \"TO DEBUG THIS PROCESS USING GDB\",")?;
+            write_debugger_setup(o, attach.debugger, &[])?;
+            writeln!(*o, "        }},")?;
+            return Ok(()) // no windows support
+        },
+    }
+    write_visualizer_files(o, meta, attach.debugger)?;
+    writeln!(*o, "            }},")?;
+    write_debugger_setup(o, attach.debugger, &[])?;
+    writeln!(*o, "        }},")?;
+    Ok(())
+}
+
+fn write_remote_launch(o: &mut impl io::Write, launch: &RemoteLaunch) -> io::Result<()> {
+    let remote = launch.remote;
+    writeln!(*o, "        {{")?;
+    writeln!(*o, "            \"name\":                     {},", serde_json::to_string(&launch.name).unwrap())?;
+    writeln!(*o, "            \"type\":                     \"cppdbg\",")?;
+    writeln!(*o, "            \"request\":                  \"launch\",")?;
+    write_presentation(o, &launch.presentation)?;
+    writeln!(*o, "            \"preLaunchTask\":            {},", serde_json::to_string(&launch.pre_launch_task).unwrap())?;
+    writeln!(*o, "            \"MIMode\":                   \"gdb\",")?;
+    match remote.gdbserver {
+        Some(port) => {
+            // local gdb, remote `gdbserver` (started over ssh)
+            let address = remote.host.rsplit('@').next().unwrap_or_default();
+            writeln!(*o, "            \"program\":                  {},", serde_json::to_string(&launch.program).unwrap())?;
+            writeln!(*o, "            \"cwd\":                      \"${{workspaceFolder}}\",")?;
+            if !launch.triple.native {
+                writeln!(*o, "            \"miDebuggerPath\":           \"gdb-multiarch\",")?;
+            }
+            writeln!(*o, "            \"miDebuggerServerAddress\":  {},", serde_json::to_string(&format!("{}:{}", address, port)).unwrap())?;
+            writeln!(*o, "            \"debugServerPath\":          \"ssh\",")?;
+            writeln!(*o, "            \"debugServerArgs\":          {},", serde_json::to_string(&format!("{} gdbserver :{} {}", remote.host, port, launch.remote_program)).unwrap())?;
+            writeln!(*o, "            \"serverStarted\":            \"Listening on port\",")?;
+        },
+        None => {
+            // remote gdb, over ssh
+            writeln!(*o, "            \"program\":                  {},", serde_json::to_string(&launch.remote_program).unwrap())?;
+            writeln!(*o, "            \"cwd\":                      {},", serde_json::to_string(&remote.path).unwrap())?;
+            writeln!(*o, "            \"pipeTransport\": {{")?;
+            writeln!(*o, "                \"pipeCwd\":              \"${{workspaceFolder}}\",")?;
+            writeln!(*o, "                \"pipeProgram\":          \"ssh\",")?;
+            writeln!(*o, "                \"pipeArgs\":             [ \"-T\", {} ],", serde_json::to_string(&remote.host).unwrap())?;
+            writeln!(*o, "                \"debuggerPath\":         \"gdb\",")?;
+            writeln!(*o, "            }},")?;
+        },
+    }
+    write_debugger_setup(o, launch.debugger, &[])?;
+    writeln!(*o, "        }},")?;
+    Ok(())
+}
+
+/// `sysroot_debugger` plus `package`'s own natvis files.
+fn package_debugger(meta: &metadata::Root, package: &metadata::PackageRef, sysroot_debugger: &Debugger) -> io::Result<Debugger> {
+    let package_dir = package.manifest_path.parent().unwrap_or(&meta.workspace_root);
+    let mut debugger = sysroot_debugger.clone();
+    match package.metadata.as_ref().and_then(|m| m.cargo_vsc.natvis.as_ref()) {
+        Some(files) => debugger.natvis.extend(files.iter().map(|file| package_dir.join(file))),
+        None        => find_natvis(package_dir, true, &mut debugger.natvis)?,
+    }
+    Ok(debugger)
+}
+
+fn write_presentation(o: &mut impl io::Write, presentation: &Presentation) -> io::Result<()> {
+    let hidden = if presentation.hidden { ", \"hidden\": true" } else { "" };
+    writeln!(*o, "            \"presentation\":             {{ \"group\": {}, \"order\": {}{} }},", serde_json::to_string(&presentation.group).unwrap(), presentation.order, hidden)
+}
+
+/// `"visualizerFile"` for the `"windows"` (cppvsdbg) block of a configuration.
+fn write_visualizer_files(o: &mut impl io::Write, meta: &metadata::Root, debugger: &Debugger) -> io::Result<()> {
+    if !debugger.natvis.is_empty() {
+        writeln!(*o, "                \"visualizerFile\":       [")?;
+        for natvis in debugger.natvis.iter() {
+            writeln!(*o, "                    {},", serde_json::to_string(&workspace_path(meta, natvis)).unwrap())?;
+        }
+        writeln!(*o, "                ],")?;
+    }
+    Ok(())
+}
+
+/// gdb pretty printers, std source paths, and symbol servers for a configuration.
+/// Write debugger setup shared by every configuration, plus gdb `setup_commands` (Linux.)
+fn write_debugger_setup(o: &mut impl io::Write, debugger: &Debugger, setup_commands: &[String]) -> io::Result<()> {
+    if debugger.gdb_pretty_printers.is_some() || !setup_commands.is_empty() {
+        writeln!(*o, "            \"linux\": {{")?;
+        writeln!(*o, "                \"setupCommands\": [")?;
+        if let Some(printers) = debugger.gdb_pretty_printers.as_ref() {
+            writeln!(*o, "                    {{ \"text\": \"-enable-pretty-printing\", \"ignoreFailures\": true }},")?;
+            writeln!(*o, "                    {{ \"text\": {}, \"ignoreFailures\": true }},", serde_json::to_string(&format!("source {}", printers.display())).unwrap())?;
+        }
+        for command in setup_commands.iter() {
+            writeln!(*o, "                    {{ \"text\": {} }},", serde_json::to_string(command).unwrap())?;
+        }
+        writeln!(*o, "                ],")?;
+        writeln!(*o, "            }},")?;
+    }
+    if !debugger.source_file_map.is_empty() {
+        writeln!(*o, "            \"sourceFileMap\": {{")?;
+        for (from, to) in debugger.source_file_map.iter() {
+            writeln!(*o, "                {}: {},", serde_json::to_string(from).unwrap(), serde_json::to_string(to).unwrap())?;
+        }
+        writeln!(*o, "            }},")?;
+    }
+    writeln!(*o, "            \"symbolOptions\": {{")?; // despite vscode intellisense errors to the contrary, this totally works
+    writeln!(*o, "                // \"cachePath\":         \"${{env:TEMP}}/SymbolCache\"")?; // this is the platform default
+    writeln!(*o, "                \"searchMicrosoftSymbolServer\": true,")?;
+    writeln!(*o, "            }},")?;
+    Ok(())
+}
+
+/// A single launch.json `"inputs"` entry.
+struct Input {
+    id:                 String,
+    description:        String,
+    default:            Option<String>,
+    options:            Option<Vec<String>>, // None: "promptString", Some: "pickString"
+}
+
+fn write_input(o: &mut impl io::Write, input: &Input) -> io::Result<()> {
+    writeln!(*o, "        {{")?;
+    writeln!(*o, "            \"id\":                       {},", serde_json::to_string(&input.id).unwrap())?;
+    writeln!(*o, "            \"type\":                     {},", if input.options.is_some() { "\"pickString\"" } else { "\"promptString\"" })?;
+    writeln!(*o, "            \"description\":              {},", serde_json::to_string(&input.description).unwrap())?;
+    if let Some(default) = input.default.as_ref() {
+        writeln!(*o, "            \"default\":                  {},", serde_json::to_string(default).unwrap())?;
+    }
+    if let Some(options) = input.options.as_ref() {
+        writeln!(*o, "            \"options\": [")?;
+        for option in options.iter() {
+            writeln!(*o, "                {},", serde_json::to_string(option).unwrap())?;
+        }
+        writeln!(*o, "            ],")?;
+    }
+    writeln!(*o, "        }},")?;
+    Ok(())
+}
+
+/// Launch configuration names & build task labels, from `[workspace.metadata.cargo-vsc.names]` templates.
+#[derive(Default)]
+struct Names {
+    templates:          metadata::MetadataNames,
+    host:               Option<String>,
+    duplicate_tasks:    Vec<String>, // `templates.task` labels shared by several build tasks, or by a fixed task
+}
+
+/// Labels of tasks `create_vscode_tasks_json` always writes (in `simple` mode or not), which templated labels mustn't duplicate.
+const FIXED_TASKS : &[&str] = &["default-build", "fetch", "cargo fetch", "cargo local-install", "check", "test", "build", "doc", "help"];
+
+/// The `{placeholder}`s of a `Names` template.
+struct NameVars<'a> {
+    package:    &'a metadata::PackageRef,
+    kind:       &'a str,
+    target:     &'a metadata::PackageTarget,
+    profile:    &'a str,            // e.g. "dev"
+    config:     &'a str,            // e.g. "debug"
+    triple:     Option<&'a str>,    // None: cargo's default
+    variant:    &'a str,            // e.g. "server" of `[[package.metadata.cargo-vsc.bin.*]] name = "server"`, "test…", or ""
+}
+
+impl Names {
+    /// Find which task labels the `task` template would duplicate, by visiting every build task `create_vscode_tasks_json` writes.
+    fn new(Context { meta, host, profiles, triples, .. }: &Context) -> Self {
+        let build_scripts = meta.metadata.as_ref().and_then(|m| m.cargo_vsc.build_scripts).unwrap_or(false);
+        let mut names = Names {
+            templates:          meta.metadata.as_ref().map(|m| m.cargo_vsc.names.clone()).unwrap_or_default(),
+            host:               host.clone(),
+            duplicate_tasks:    Vec::new(),
+        };
+        if names.templates.task.is_none() { return names } // commands are already unique
+
+        let mut labels = Vec::new();
+        for package in meta.packages.iter().filter(|package| meta.workspace_members.contains(&package.id)) {
+            for target in package.targets.iter() {
+                let templated = |kind: &str| cargo_build(package, target, kind).is_some() || library_host(package, target, kind).is_some() || (kind == "custom-build" && build_scripts);
+                for kind in target.kind.iter().filter(|kind| templated(kind)) {
+                    for profile in profiles.iter() {
+                        for triple in triples.iter() {
+                            labels.push(names.render_task(&NameVars::new(package, kind, target, profile, triple)));
+                        }
+                    }
+                }
+                if let Some((kind, _)) = test_harness(package, target) {
+                    labels.push(names.render_task(&NameVars::test_harness(package, kind, target, triples)));
+                }
+            }
+        }
+        let mut reserved = FIXED_TASKS.iter().map(|label| label.to_string()).collect::<Vec<_>>();
+        for package in meta.packages.iter().filter(|package| meta.workspace_members.contains(&package.id)) {
+            reserved.extend(package.targets.iter().map(local_doc_label));
+            reserved.extend(["repository", "documentation", "homepage"].iter().map(|link| open_link_label(link, package)));
+        }
+        for compound in meta.metadata.as_ref().into_iter().flat_map(|m| m.cargo_vsc.compound.keys()) {
+            reserved.push(compound_build_label(compound));
+        }
+
+        labels.sort();
+        for pair in labels.windows(2) {
+            if pair[0] == pair[1] && !names.duplicate_tasks.contains(&pair[0]) {
+                names.duplicate_tasks.push(pair[0].clone());
+            }
+        }
+        for label in labels {
+            if reserved.contains(&label) && !names.duplicate_tasks.contains(&label) {
+                names.duplicate_tasks.push(label);
+            }
+        }
+        names
+    }
+
+    fn render(&self, template: &str, vars: &NameVars) -> String {
+        template
+            .replace("{package}",   &vars.package.name)
+            .replace("{kind}",      vars.kind)
+            .replace("{target}",    &vars.target.name)
+            .replace("{profile}",   vars.profile)
+            .replace("{triple}",    vars.triple.or(self.host.as_deref()).unwrap_or_default())
+            .replace("{variant}",   vars.variant)
+    }
+
+    fn render_task(&self, vars: &NameVars) -> String {
+        self.render(self.templates.task.as_deref().unwrap_or_default(), vars)
+    }
+
+    /// The label of the build task running `cmd`.  Unique, lest VS Code silently drop tasks.
+    fn task(&self, cmd: &str, vars: &NameVars) -> String {
+        if self.templates.task.is_none() { return cmd.into() }
+        let label = self.render_task(vars);
+        if self.duplicate_tasks.contains(&label) { format!("{} ({})", label, cmd) } else { label }
+    }
+
+    /// The name of a launch configuration.  Not necessarily unique: see `Unique`.
+    fn launch(&self, single_member_package: bool, vars: &NameVars) -> String {
+        if let Some(template) = self.templates.launch.as_ref() {
+            let mut name = self.render(template, vars);
+            if !vars.variant.is_empty() && !template.contains("{variant}") {
+                name = format!("{} ({})", name, vars.variant); // lest variants differ only by `Unique` suffixes
+            }
+            return name;
+        }
+        if vars.kind == "compound" {
+            return format!("{} • {}", vars.variant, vars.target.name);
+        }
+        let mut name = String::new();
+        if !single_member_package {
+            name.push_str(&vars.package.name);
+            name.push_str(" • ");
+        }
+        if vars.kind == "custom-build" {
+            name.push_str("build script • "); // rather than "custom-build • build-script-build • "
+        } else if vars.kind != "bin" {
+            name.push_str(vars.kind);
+            name.push_str(" • ");
+        }
+        if vars.package.name.replace('-', "_") != vars.target.name.replace('-', "_") && vars.kind != "custom-build" {
+            name.push_str(&vars.target.name);
+            name.push_str(" • ");
+        }
+        name.push_str(vars.config);
+        if let Some(triple) = vars.triple {
+            name.push_str(" • ");
+            name.push_str(triple);
+        }
+        if !vars.variant.is_empty() {
+            name.push_str(" • ");
+            name.push_str(vars.variant);
+        }
+        name
+    }
+}
+
+impl<'a> NameVars<'a> {
+    fn new(package: &'a metadata::PackageRef, kind: &'a str, target: &'a metadata::PackageTarget, profile: &'a Profile, triple: &'a Triple) -> Self {
+        NameVars { package, kind, target, profile: &profile.name, config: profile.dir(), triple: triple.name.as_deref(), variant: "" }
+    }
+
+    /// Test harnesses are built by `cargo test` / `cargo bench` for cargo's default triple.
+    fn test_harness(package: &'a metadata::PackageRef, kind: &'a str, target: &'a metadata::PackageTarget, triples: &'a [Triple]) -> Self {
+        let (profile, config) = if kind == "bench" { ("bench", "release") } else { ("test", "debug") };
+        NameVars { package, kind, target, profile, config, triple: triples[0].name.as_deref(), variant: "" }
+    }
+}
+
+/// Disambiguates duplicate launch configuration names with ` (2)`, ` (3)`, ... suffixes.
+#[derive(Default)]
+struct Unique(BTreeMap<String, usize>);
+
+impl Unique {
+    fn name(&mut self, name: String) -> String {
+        let count = self.0.entry(name.clone()).or_insert(0);
+        *count += 1;
+        if *count == 1 { name } else { format!("{} ({})", name, count) }
+    }
+}
+
+/// `(label, cmd, env)` to run `cmd` (e.g. `cargo build ...`) on nightly with `-Zsanitizer={sanitizer}`, in a separate target directory.
+/// `build` is the label of the task running `cmd` unsanitized.
+fn sanitizer_cmd(meta: &metadata::Root, sanitizer: &str, cmd: &str, build: &str, triple: &str) -> (String, String, Env) {
+    let mut cmd = cmd.replacen("cargo ", "cargo +nightly ", 1);
+    if !cmd.contains(" --target ") {
+        cmd.push_str(" --target "); // keeps build scripts & proc macros uninstrumented
+        cmd.push_str(triple);
+    }
+    if sanitizer == "thread" || sanitizer == "memory" {
+        cmd.push_str(" -Zbuild-std"); // an uninstrumented std reports false positives (requires the nightly `rust-src` component)
+    }
+    let env = vec![
+        ("RUSTFLAGS".to_string(),           format!("-Zsanitizer={}", sanitizer)),
+        ("CARGO_TARGET_DIR".to_string(),    workspace_path(meta, &sanitizer_dir(meta, sanitizer))),
+    ];
+    (format!("{} sanitizer ({})", sanitizer, build), cmd, env)
+}
+
+/// Known `-Zsanitizer=...` values.
+const SANITIZERS : &[&str] = &["address", "cfi", "dataflow", "hwaddress", "kcfi", "kernel-address", "leak", "memory", "memtag", "safestack", "shadow-call-stack", "thread"];
+
+/// `{target}/sanitize-{sanitizer}`, lest sanitized builds clobber regular ones.
+fn sanitizer_dir(meta: &metadata::Root, sanitizer: &str) -> PathBuf {
+    meta.target_directory.join(format!("sanitize-{}", sanitizer))
+}
+
+/// The workspace member `package` & `bin` target a `[workspace.metadata.cargo-vsc.compound]` `member` refers to.
+fn compound_member<'a>(meta: &'a metadata::Root, member: &metadata::MetadataCompoundMember) -> Option<(&'a metadata::PackageRef, &'a metadata::PackageTarget)> {
+    find_bin(meta, member.package.as_deref(), &member.bin)
+}
+
+/// The workspace member (named `package`, if specified) with a `bin` target.
+fn find_bin<'a>(meta: &'a metadata::Root, package: Option<&str>, bin: &str) -> Option<(&'a metadata::PackageRef, &'a metadata::PackageTarget)> {
+    meta.packages.iter()
+        .filter(|p| meta.workspace_members.contains(&p.id))
+        .filter(|p| package.is_none_or(|name| name == p.name))
+        .find_map(|p| Some((p, p.targets.iter().find(|t| t.name == bin && t.kind.iter().any(|k| k == "bin"))?)))
+}
+
+/// `"cargo build -vv --package {package} ..."` to build (and run) `package`'s build script, logging its environment.
+fn build_script_build(package: &metadata::PackageRef, profile: &Profile, triple: &Triple) -> String {
+    profile.cmd(&format!("cargo build -vv --package {}", package.name), triple)
+}
+
+/// `cargo build --package {package}` arguments (sans `-vv --message-format=json`) to build and run `package`'s build script.
+fn build_script_args<'a>(package: &'a metadata::PackageRef, profile: &'a Profile, triple: &'a Triple) -> Vec<&'a str> {
+    [&["build", "--package", package.name.as_str()][..], &profile.args(), &triple.args()].concat()
+}
+
+/// `CARGO_PROFILE_{NAME}_BUILD_OVERRIDE_DEBUG`, to set to `true`: build scripts are otherwise built without debug info.
+fn build_override_debug_var(profile: &Profile) -> String {
+    format!("CARGO_PROFILE_{}_BUILD_OVERRIDE_DEBUG", profile.name.to_uppercase().replace('-', "_"))
+}
+
+/// `[package.metadata.cargo-vsc.host]`, if `kind` is the (first) cdylib/dylib kind of `target` and one is configured.
+fn library_host<'a>(package: &'a metadata::PackageRef, target: &metadata::PackageTarget, kind: &str) -> Option<&'a metadata::MetadataHost> {
+    let first_dylib_kind = target.kind.iter().find(|k| DYLIB_KINDS.contains(&k.as_str()))?;
+    if first_dylib_kind != kind { return None }
+    package.metadata.as_ref()?.cargo_vsc.host.as_ref()
+}
+
+/// `(program, windows_program)` of a library's `host`, with `{dir}` standing in for the profile's output directory of workspace bins.
+fn host_program(meta: &metadata::Root, package: &metadata::PackageRef, host: &metadata::MetadataHost) -> Option<(String, String)> {
+    if let Some(program) = host.program.as_ref() {
+        let program = workspace_path(meta, &package.manifest_path.parent().unwrap_or(&meta.workspace_root).join(program));
+        return Some((program.clone(), program));
+    }
+    let (_, target) = find_bin(meta, host.package.as_deref(), host.bin.as_deref()?)?;
+    Some((format!("{{dir}}/{}", target.name), format!("{{dir}}/{}.exe", target.name)))
+}
+
+/// `"cargo build --package {package} --lib ..."`, plus the `host` bin (if it's part of the workspace).
+fn host_build(meta: &metadata::Root, package: &metadata::PackageRef, host: &metadata::MetadataHost, profile: &Profile, triple: &Triple) -> String {
+    let mut cmd = format!("cargo build --package {} --lib", package.name);
+    if let Some((host_package, target)) = host.program.is_none().then(|| find_bin(meta, host.package.as_deref(), host.bin.as_deref()?)).flatten() {
+        if host_package.id != package.id {
+            cmd.push_str(&format!(" --package {}", host_package.name));
+        }
+        cmd.push_str(&format!(" --bin {}", target.name));
+        if !target.required_features.is_empty() {
+            let features = target.required_features.iter().map(|feature| format!("{}/{}", host_package.name, feature)).collect::<Vec<_>>();
+            cmd.push_str(&format!(" --features {}", features.join(",")));
+        }
+    }
+    profile.cmd(&cmd, triple)
+}
+
+/// `{name}.dll`, `lib{name}.dylib`, or `lib{name}.so`, depending on `triple` (or the host.)
+fn dylib_filename(name: &str, triple: Option<&str>) -> String {
+    let name = name.replace('-', "_");
+    let windows = triple.map_or(cfg!(windows),                  |triple| triple.contains("windows"));
+    let apple   = triple.map_or(cfg!(target_os = "macos"),      |triple| triple.contains("apple"));
+    if windows      { format!("{}.dll", name) }
+    else if apple   { format!("lib{}.dylib", name) }
+    else            { format!("lib{}.so", name) }
+}
+
+/// `"cargo build --package ... --bin ..."` to build every member of a compound at once, for `triple`.
+fn compound_build(meta: &metadata::Root, members: &[metadata::MetadataCompoundMember], triple: &Triple) -> String {
+    let mut packages = Vec::new();
+    let mut bins = String::new();
+    let mut features = Vec::new();
+    for (package, target) in members.iter().filter_map(|member| compound_member(meta, member)) {
+        if !packages.contains(&package.name.as_str()) { packages.push(&package.name) }
+        bins.push_str(&format!(" --bin {}", target.name));
+        features.extend(target.required_features.iter().map(|feature| format!("{}/{}", package.name, feature)));
+    }
+    let mut cmd = String::from("cargo build");
+    for package in packages {
+        cmd.push_str(" --package ");
+        cmd.push_str(package);
+    }
+    cmd.push_str(&bins);
+    for arg in triple.args() {
+        cmd.push(' ');
+        cmd.push_str(arg);
+    }
+    if !features.is_empty() {
+        cmd.push_str(" --features ");
+        cmd.push_str(&features.join(","));
+    }
+    cmd
+}
+
+/// The triple `remote` bins are built for: `remote.triple`, or cargo's default.
+fn remote_triple<'a>(remote: &metadata::MetadataRemote, triples: &'a [Triple], host: Option<&str>) -> Option<&'a Triple> {
+    match remote.triple.as_deref() {
+        None            => triples.first(),
+        Some(triple)    => triples.iter().find(|t| t.name.as_deref().or(host) == Some(triple)),
+    }
+}
+
+/// `(label, cmd)` to copy `program` (built by `cargo_build`) to `remote`.
+fn remote_deploy(meta: &metadata::Root, remote: &metadata::MetadataRemote, program: &Path, build: &str) -> (String, String) {
+    let cmd = format!("ssh {host} mkdir -p \"{path}\" && scp \"{program}\" {host}:\"{path}/\"", host = remote.host, path = remote.path, program = workspace_path(meta, program)); // `${workspaceFolder}` may contain spaces
+    (format!("deploy ({})", build), cmd)
+}
+
+/// `ASAN_OPTIONS`, `TSAN_OPTIONS`, etc.
+fn sanitizer_options_var(sanitizer: &str) -> Option<&'static str> {
+    match sanitizer {
+        "address"   => Some("ASAN_OPTIONS"),
+        "thread"    => Some("TSAN_OPTIONS"),
+        "memory"    => Some("MSAN_OPTIONS"),
+        "leak"      => Some("LSAN_OPTIONS"),
+        _other      => None,
+    }
+}
+
+/// Is `package` built by a plain `cargo build` in the workspace root?  (In `default-members`, or there are none.)
+fn is_default_member(meta: &metadata::Root, package: &metadata::PackageRef) -> bool {
+    meta.workspace_default_members.is_empty() || meta.workspace_default_members.contains(&package.id)
+}
+
+/// Is `target` what `cargo run --package {package}` would run?  (`default-run`, or the only bin.)
+fn is_default_run(package: &metadata::PackageRef, target: &metadata::PackageTarget) -> bool {
+    let is_bin = |target: &metadata::PackageTarget| target.kind.iter().any(|k| k == "bin");
+    is_bin(target) && match package.default_run.as_ref() {
+        Some(default_run)   => *default_run == target.name,
+        None                => package.targets.iter().filter(|t| is_bin(t)).count() == 1,
+    }
+}
+
+const LIB_KINDS : &[&str] = &["lib", "rlib", "dylib", "cdylib", "staticlib", "proc-macro"];
+const DYLIB_KINDS : &[&str] = &["cdylib", "dylib"];
+
+/// `(kind, "cargo test --no-run ...")` to build `target`'s libtest harness, if it has one.
+fn test_harness(package: &metadata::PackageRef, target: &metadata::PackageTarget) -> Option<(&'static str, String)> {
+    let (kind, subcommand, selector) = harness_selector(target)?;
+    let cmd = format!("cargo {} --no-run --package {} {}", subcommand, package.name, selector.join(" "));
+    Some((kind, with_required_features(cmd, target)))
+}
+
+/// `(kind, subcommand, selector)`: `target`'s libtest harness is built by `cargo {subcommand} --no-run --package ... {selector...}`.
+fn harness_selector(target: &metadata::PackageTarget) -> Option<(&'static str, &'static str, Vec<&str>)> {
+    let is = |kind: &str| target.kind.iter().any(|k| k == kind);
+    if is("test") {
+        Some(("test", "test", vec!["--test", &target.name]))
+    } else if is("bench") {
+        Some(("bench", "bench", vec!["--bench", &target.name]))
+    } else if target.kind.iter().any(|k| LIB_KINDS.contains(&k.as_str())) && target.test.unwrap_or(true) {
+        Some(("lib", "test", vec!["--lib"]))
+    } else {
+        None
+    }
+}
+
+/// `(subdir, "cargo build ...")` to build a launchable `kind` of `target`.
+fn cargo_build(package: &metadata::PackageRef, target: &metadata::PackageTarget, kind: &str) -> Option<(&'static str, String)> {
+    let (subdir, cmd) = match kind {
+        "example"   => ("examples/", format!("cargo build --package {} --example {}", package.name, target.name)),
+        "bin"       => ("",          format!("cargo build --package {} --bin {}", package.name, target.name)),
+        _other      => return None, // not currently launchable
+    };
+    Some((subdir, with_required_features(cmd, target)))
+}
+
+/// `cmd --features {target.required_features}`, lest cargo refuse to build `target`.
+fn with_required_features(mut cmd: String, target: &metadata::PackageTarget) -> String {
+    if !target.required_features.is_empty() {
+        cmd.push_str(" --features ");
+        cmd.push_str(&target.required_features.join(","));
+    }
+    cmd
+}
+
+/// Run e.g. `cargo build --package ... --message-format=json` to discover the real (possibly hashed) artifact paths of `packages`.
+fn probe_artifacts<'a>(packages: impl Iterator<Item = &'a metadata::PackageRef>, args: &[&str]) -> io::Result<Vec<artifacts::Artifact>> {
+    let mut args = args.to_vec();
+    let mut features = Vec::new();
+    for package in packages {
+        args.push("--package");
+        args.push(&package.name);
+        for feature in package.targets.iter().flat_map(|t| t.required_features.iter()).filter(|f| !f.contains('/')) {
+            let feature = format!("{}/{}", package.name, feature);
+            if !features.contains(&feature) { features.push(feature) }
+        }
+    }
+    let features = features.join(",");
+    if !features.is_empty() {
+        args.push("--features");
+        args.push(&features);
+    }
+    artifacts::Artifact::probe(&args, &[])
+}
+
+/// Run `cargo {test|bench} --no-run --message-format=json` to discover the hashed libtest harness executables of `packages`.
+///
+/// Harnesses are probed with the same `--package` & `--features` as their `test_harness` build tasks: either affects the hashes.
+/// With `sanitizer` = `Some((sanitizer, triple))`, the (non-bench) harnesses are probed as built by `sanitizer_cmd` instead.
+fn probe_harnesses<'a>(meta: &metadata::Root, packages: impl Iterator<Item = &'a metadata::PackageRef>, sanitizer: Option<(&str, &str)>) -> Vec<artifacts::Artifact> {
+    let mut probes = BTreeMap::<(&str, &str, String), Vec<&str>>::new();
+    for package in packages {
+        for target in package.targets.iter() {
+            if let Some((_kind, subcommand, selector)) = harness_selector(target).filter(|(kind, _, _)| sanitizer.is_none() || *kind != "bench") {
+                probes.entry((subcommand, package.name.as_str(), target.required_features.join(","))).or_default().extend(selector);
+            }
+        }
+    }
+
+    let mut artifacts = Vec::new();
+    for ((subcommand, package, features), selectors) in probes.iter() {
+        let mut cmd = format!("cargo {} --no-run --package {} {}", subcommand, package, selectors.join(" "));
+        if !features.is_empty() {
+            cmd = format!("{} --features {}", cmd, features);
+        }
+        let (cmd, env) = match sanitizer {
+            Some((sanitizer, triple))   => {
+                let (_, cmd, env) = sanitizer_cmd(meta, sanitizer, &cmd, "", triple);
+                let workspace_root = meta.workspace_root.display().to_string();
+                (cmd, env.into_iter().map(|(name, value)| (name, value.replace("${workspaceFolder}", &workspace_root))).collect()) // as VS Code would
+            },
+            None                        => (cmd, Vec::new()),
+        };
+        let args = cmd.split_whitespace().skip(1).collect::<Vec<_>>(); // package names, selectors, and features never contain spaces
+        match artifacts::Artifact::probe(&args, &env) {
+            Ok(probed) => artifacts.extend(probed),
+            Err(err) => eprintln!("warning: unable to locate {}test executables of {:?}, skipping their launch configurations: {}", sanitizer.map_or_else(String::new, |(s, _)| format!("{} sanitizer ", s)), package, err),
+        }
+    }
+    artifacts
+}
+
+/// The first artifact with an executable built for `target` (with `test` = true for libtest harnesses.)
+fn find_executable<'a>(artifacts: &'a [artifacts::Artifact], package: &metadata::PackageRef, target: &metadata::PackageTarget, test: bool) -> Option<(&'a artifacts::Artifact, &'a Path)> {
+    artifacts.iter()
+        .filter(|a| a.package_id == package.id && a.profile.test == test && a.target.name == target.name && a.target.kind == target.kind)
+        .find_map(|a| Some((a, a.executable.as_deref()?)))
+}
+
+/// `path`, relative to `${workspaceFolder}` if possible.
+fn workspace_path(meta: &metadata::Root, path: &Path) -> String {
+    match path.strip_prefix(&meta.workspace_root) {
+        Ok(rel) if rel.as_os_str().is_empty() => "${workspaceFolder}".into(),
+        Ok(rel)     => format!("${{workspaceFolder}}/{}", rel.display()).replace('\\', "/"),
+        Err(_)      => path.display().to_string(),
+    }
+}
+
+/// Collect `*.natvis` files in `dir`, optionally recursing into subdirectories that aren't `target`, hidden, or other packages.
+fn find_natvis(dir: &Path, recurse: bool, natvis: &mut Vec<PathBuf>) -> io::Result<()> {
+    let mut entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries.collect::<io::Result<Vec<_>>>()?,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+    entries.sort_by_key(|e| e.file_name());
+
+    for entry in entries {
+        let path = entry.path();
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if entry.file_type()?.is_dir() {
+            if !recurse || name.starts_with('.') || name == "target" || path.join("Cargo.toml").exists() { continue }
+            find_natvis(&path, recurse, natvis)?;
+        } else if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("natvis")) {
+            natvis.push(path);
+        }
+    }
+    Ok(())
+}
+
 
 
-fn create_vscode_tasks_json(Context { meta, vscode, .. }: &Context) -> io::Result<()> {
+fn create_vscode_tasks_json(Context { meta, vscode, host, profiles, triples, names, .. }: &Context) -> io::Result<()> {
     let path = vscode.join("tasks.json");
     let mut o = create_json(&path)?;
 
@@ -175,6 +1687,35 @@ fn create_vscode_tasks_json(Context { meta, vscode, .. }: &Context) -> io::Resul
     // TODO: also install for packages: meta.packages.iter().any(|p| meta.workspace_members.contains(&p.id) && p.manifest.toml.metadata.local_install.is_some());
 
     let simple = meta.metadata.as_ref().and_then(|m| m.cargo_vsc.simple).unwrap_or(true);
+    let rr = meta.metadata.as_ref().and_then(|m| m.cargo_vsc.rr).unwrap_or(false);
+    let valgrind = meta.metadata.as_ref().and_then(|m| m.cargo_vsc.valgrind).unwrap_or(false);
+    let sanitizers = meta.metadata.as_ref().and_then(|m| m.cargo_vsc.sanitizers.clone()).unwrap_or_default();
+    let build_scripts = meta.metadata.as_ref().and_then(|m| m.cargo_vsc.build_scripts).unwrap_or(false);
+    let mut runners = Vec::new();
+    if rr       { runners.push(("rr record", "rr record")) }
+    if valgrind { runners.push(("valgrind",  "valgrind --leak-check=full")) }
+    // `cargo run` / `cargo test` with e.g. `rr record` as the runner, so only the target itself gets recorded (not cargo)
+    let with_runner = |(name, runner): (&str, &str), cmd: String, build: &str, triple: &Triple, env: &[(String, String)]| -> Option<(String, String, Env)> {
+        let mut env = env.to_vec();
+        set_env(&mut env, &cargo_config::runner_var(triple.name.as_deref().or(host.as_deref())?), runner);
+        Some((format!("{} ({})", name, build), cmd, env))
+    };
+    let remote = meta.metadata.as_ref().and_then(|m| m.cargo_vsc.remote.as_ref());
+    let with_sanitizer = |sanitizer: &str, cmd: &str, build: &str, triple: &Triple, env: &[(String, String)]| -> Option<(String, String, Env)> {
+        let (label, cmd, sanitizer_env) = sanitizer_cmd(meta, sanitizer, cmd, build, triple.name.as_deref().or(host.as_deref())?);
+        let mut env = env.to_vec();
+        for (name, value) in sanitizer_env {
+            set_env(&mut env, &name, &value);
+        }
+        Some((label, cmd, env))
+    };
+    // "Run Build Task" (Ctrl+Shift+B) builds `cargo run`'s bin (debug, first triple) by default, "Run Test Task" runs the default members' tests
+    let default_run = meta.packages.iter()
+        .filter(|p| meta.workspace_members.contains(&p.id) && is_default_member(meta, p))
+        .find_map(|p| Some((&p.id, &p.targets.iter().find(|t| is_default_run(p, t))?.name)));
+    let default_profile = profiles.iter().position(|p| p.dir() == "debug").unwrap_or(0);
+    let default_build = if default_run.is_some() { "\"build\"" } else { "{ \"kind\": \"build\", \"isDefault\": true }" };
+    let default_test = "{ \"kind\": \"test\", \"isDefault\": true }";
 
     writeln!(o, "{{")?;
     writeln!(o, "    \"version\":          \"2.0.0\",")?;
@@ -183,6 +1724,10 @@ fn create_vscode_tasks_json(Context { meta, vscode, .. }: &Context) -> io::Resul
     writeln!(o, "    \"presentation\": {{")?;
     writeln!(o, "        \"clear\":        true,")?;
     writeln!(o, "    }},")?;
+    let workspace_env = env_file(meta, None).map_or_else(Vec::new, |path| read_env_file(&path));
+    if !workspace_env.is_empty() {
+        writeln!(o, "    \"options\":          {{ \"env\": {} }},", env_json(&workspace_env))?;
+    }
     writeln!(o, "    \"tasks\": [")?;
     if simple {
         writeln!(o, "        {{")?;
@@ -191,7 +1736,7 @@ fn create_vscode_tasks_json(Context { meta, vscode, .. }: &Context) -> io::Resul
         if has_any_local_install {
             writeln!(o, "            \"dependsOn\":        [\"cargo local-install\"],")?;
         }
-        writeln!(o, "            \"group\":            {{ \"kind\": \"build\", \"isDefault\": true }},")?;
+        writeln!(o, "            \"group\":            {},", if default_run.is_some() { default_test } else { default_build })?; // `cargo test` builds everything
         writeln!(o, "        }},")?;
         writeln!(o, "        {{")?;
         writeln!(o, "            \"label\":            \"help\",")?;
@@ -212,7 +1757,7 @@ fn create_vscode_tasks_json(Context { meta, vscode, .. }: &Context) -> io::Resul
         writeln!(o, "            \"label\":            \"default-build\",")?;
         writeln!(o, "            \"dependsOrder\":     \"sequence\",")?;
         writeln!(o, "            \"dependsOn\":        [ \"fetch\", \"check\", \"test\", \"build\", \"doc\" ],")?;
-        writeln!(o, "            \"group\":            {{ \"kind\": \"build\", \"isDefault\": true }}")?;
+        writeln!(o, "            \"group\":            {}", default_build)?;
         writeln!(o, "        }},")?;
         writeln!(o)?;
         writeln!(o)?;
@@ -256,6 +1801,7 @@ fn create_vscode_tasks_json(Context { meta, vscode, .. }: &Context) -> io::Resul
         writeln!(o, "        {{")?;
         writeln!(o, "            \"label\":            \"test\",")?;
         writeln!(o, "            \"command\":          \"cargo t --frozen\",")?;
+        writeln!(o, "            \"group\":            {},", default_test)?;
         writeln!(o, "            \"presentation\":     {{ \"clear\": true, \"group\": \"test\", \"reveal\": \"always\" }},")?;
         writeln!(o, "            \"problemMatcher\":   {{ \"base\": \"$rustc\", \"owner\": \"test\", \"source\": \"test\" }},")?;
         writeln!(o, "        }},")?;
@@ -302,23 +1848,101 @@ fn create_vscode_tasks_json(Context { meta, vscode, .. }: &Context) -> io::Resul
         writeln!(o)?;
         writeln!(o, "        // {}", package.name)?;
 
+        let mut package_env = workspace_env.clone();
+        if let Some(path) = env_file(meta, Some(package)).filter(|path| Some(path) != env_file(meta, None).as_ref()) {
+            for (name, value) in read_env_file(&path) {
+                set_env(&mut package_env, &name, &value);
+            }
+        }
+        let task_env = |env: Env| if env == workspace_env { Vec::new() } else { env }; // else inherited from the top level `"options"`
+
         for target in package.targets.iter() {
             for kind in target.kind.iter() {
-                let cargo_build_debug = match kind.as_str() {
-                    "example"   => format!("cargo build --package {} --example {}", package.name, target.name),
-                    "bin"       => format!("cargo build --package {} --bin {}", package.name, target.name),
-                    _other      => continue // not currently launchable
+                if kind == "custom-build" && build_scripts {
+                    for profile in profiles.iter() {
+                        for triple in triples.iter() {
+                            let (mut env, osx_env) = profile.env(&package_env);
+                            set_env(&mut env, &build_override_debug_var(profile), "true");
+                            let cmd = build_script_build(package, profile, triple);
+                            write_task(&mut o, &names.task(&cmd, &NameVars::new(package, kind, target, profile, triple)), &cmd, &task_env(env), &osx_env, None)?;
+                        }
+                    }
+                }
+                if let Some(host) = library_host(package, target, kind) {
+                    for profile in profiles.iter() {
+                        for triple in triples.iter() {
+                            let (env, osx_env) = profile.env(&package_env);
+                            let cmd = host_build(meta, package, host, profile, triple);
+                            write_task(&mut o, &names.task(&cmd, &NameVars::new(package, kind, target, profile, triple)), &cmd, &task_env(env), &osx_env, None)?;
+                        }
+                    }
+                }
+                let cargo_build = match cargo_build(package, target, kind) {
+                    Some((_subdir, cargo_build)) => cargo_build,
+                    None => continue // not currently launchable
                 };
-                let cargo_build_release = format!("{} --release", cargo_build_debug);
-                write_cmd(&mut o, &cargo_build_debug)?;
-                write_cmd(&mut o, &cargo_build_release)?;
+                for (p, profile) in profiles.iter().enumerate() {
+                    for (t, triple) in triples.iter().enumerate() {
+                        let (env, osx_env) = profile.env(&package_env);
+                        let cmd = profile.cmd(&cargo_build, triple);
+                        let label = names.task(&cmd, &NameVars::new(package, kind, target, profile, triple));
+                        let is_default = default_run == Some((&package.id, &target.name)) && p == default_profile && t == 0;
+                        let group = if is_default { Some(("build", true)) } else { None };
+                        write_task(&mut o, &label, &cmd, &task_env(env), &osx_env, group)?;
+                    }
+                }
+                if kind != "bin" { continue }
+                if let Some((remote, triple)) = remote.and_then(|remote| Some((remote, remote_triple(remote, triples, host.as_deref())?))) {
+                    for profile in profiles.iter() {
+                        let program = triple.dir(meta).join(profile.dir()).join(&target.name);
+                        let build = names.task(&profile.cmd(&cargo_build, triple), &NameVars::new(package, kind, target, profile, triple));
+                        let (label, cmd) = remote_deploy(meta, remote, &program, &build);
+                        write_deploy(&mut o, &label, &cmd, &build)?;
+                    }
+                }
+                for profile in profiles.iter() {
+                    for triple in triples.iter().filter(|triple| triple.native && triple.runner.is_none()) {
+                        let (env, _) = profile.env(&package_env);
+                        let cargo_build = profile.cmd(&cargo_build, triple);
+                        let build = names.task(&cargo_build, &NameVars::new(package, kind, target, profile, triple));
+                        for runner in runners.iter().copied() {
+                            if let Some((label, cmd, env)) = with_runner(runner, cargo_build.replacen("cargo build", "cargo run", 1), &build, triple, &env) {
+                                write_task(&mut o, &label, &cmd, &task_env(env), &[], None)?;
+                            }
+                        }
+                        for sanitizer in sanitizers.iter() {
+                            if let Some((label, cmd, env)) = with_sanitizer(sanitizer, &cargo_build, &build, triple, &env) {
+                                write_task(&mut o, &label, &cmd, &task_env(env), &[], None)?;
+                            }
+                        }
+                    }
+                }
+            }
+
+            if let Some((kind, cargo_test_no_run)) = test_harness(package, target) {
+                let label = names.task(&cargo_test_no_run, &NameVars::test_harness(package, kind, target, triples));
+                write_task(&mut o, &label, &cargo_test_no_run, &task_env(package_env.clone()), &[], None)?;
+                if kind != "bench" {
+                    let cargo_test = cargo_test_no_run.replacen(" --no-run", "", 1);
+                    for runner in runners.iter().copied() {
+                        if let Some((label, cmd, env)) = with_runner(runner, cargo_test.clone(), &label, &triples[0], &package_env) {
+                            write_task(&mut o, &label, &cmd, &task_env(env), &[], None)?;
+                        }
+                    }
+                    for sanitizer in sanitizers.iter() {
+                        if let Some((label, cmd, env)) = with_sanitizer(sanitizer, &cargo_test_no_run, &label, &triples[0], &package_env) {
+                            write_task(&mut o, &label, &cmd, &task_env(env), &[], None)?;
+                        }
+                    }
+                }
             }
 
             if !simple {
                 // XXX: dedupe tasks? if you have an rlib and a bin sharing the same target name, you'll only get docs for one, but open link tasks for both.
                 // OTOH VSC itself seems to deduplicate the tasks itself so maybe that's fine...
-                let local_doc_open = format!("build & open local documentation ({})", target.name);
-                write_open_link(&mut o, &local_doc_open, &format!("${{workspaceFolder}}\\target\\doc\\{}\\index.html", target.name.replace('-', "_")), "doc")?;
+                let local_doc_open = local_doc_label(target);
+                let local_doc = meta.target_directory.join("doc").join(target.name.replace('-', "_")).join("index.html");
+                write_open_link(&mut o, &local_doc_open, &workspace_path(meta, &local_doc), "doc")?;
             }
         }
 
@@ -328,20 +1952,35 @@ fn create_vscode_tasks_json(Context { meta, vscode, .. }: &Context) -> io::Resul
             ("homepage",        package.homepage        .as_deref()),
         ] {
             if let Some(link) = link {
-                write_open_link(&mut o, &format!("open {label} ({})", package.name), link, "")?;
+                write_open_link(&mut o, &open_link_label(label, package), link, "")?;
             }
         }
     }
 
+    if let Some(metadata) = meta.metadata.as_ref().filter(|m| !m.cargo_vsc.compound.is_empty()) {
+        writeln!(o)?;
+        writeln!(o)?;
+        writeln!(o)?;
+        writeln!(o, "        // compounds")?;
+        for (compound, members) in metadata.cargo_vsc.compound.iter() {
+            write_task(&mut o, &compound_build_label(compound), &compound_build(meta, members, &triples[0]), &[], &[], None)?;
+        }
+    }
+
     writeln!(o, "    ]")?; // tasks
     writeln!(o, "}}")?;
     Ok(())
 }
 
+fn local_doc_label(target: &metadata::PackageTarget) -> String { format!("build & open local documentation ({})", target.name) }
+fn open_link_label(link: &str, package: &metadata::PackageRef) -> String { format!("open {} ({})", link, package.name) }
+fn compound_build_label(compound: &str) -> String { format!("build compound ({})", compound) } // not the command, which may match a bin's build task
+
 fn write_open_link(o: &mut impl io::Write, title: &str, url: &str, depends_on: &str) -> io::Result<()> {
+    let windows_url = if url.contains("://") { url.to_string() } else { url.replace('/', "\\") }; // local paths need backslashes for `start`
     writeln!(*o, "        {{")?;
     writeln!(*o, "            \"label\":            {},", serde_json::to_string(title).unwrap())?;
-    writeln!(*o, "            \"windows\":          {{ \"command\": {} }},", serde_json::to_string(&format!("start \"\"    \"{}\"", url)).unwrap())?;
+    writeln!(*o, "            \"windows\":          {{ \"command\": {} }},", serde_json::to_string(&format!("start \"\"    \"{}\"", windows_url)).unwrap())?;
     writeln!(*o, "            \"linux\":            {{ \"command\": {} }},", serde_json::to_string(&format!("xdg-open      \"{}\"", url)).unwrap())?;
     writeln!(*o, "            \"osx\":              {{ \"command\": {} }},", serde_json::to_string(&format!("open          \"{}\"", url)).unwrap())?;
     writeln!(*o, "            \"presentation\":     {{ \"clear\": true, \"panel\": \"shared\", \"reveal\": \"silent\" }},")?;
@@ -352,12 +1991,82 @@ fn write_open_link(o: &mut impl io::Write, title: &str, url: &str, depends_on: &
     Ok(())
 }
 
-fn write_cmd(o: &mut impl io::Write, cmd: &str) -> io::Result<()> {
-    let cmd = serde_json::to_string(cmd).unwrap();
+/// Write a task labeled `label` running `cmd` with `env` (+ `osx_env` on macOS) on top of the top level `"options"`, in `group` (`(kind, is_default)`).
+fn write_task(o: &mut impl io::Write, label: &str, cmd: &str, env: &[(String, String)], osx_env: &[(String, String)], group: Option<(&str, bool)>) -> io::Result<()> {
+    writeln!(*o, "        {{")?;
+    writeln!(*o, "            \"label\":            {},", serde_json::to_string(label).unwrap())?;
+    writeln!(*o, "            \"command\":          {},", serde_json::to_string(cmd).unwrap())?;
+    if !env.is_empty() {
+        writeln!(*o, "            \"options\":          {{ \"env\": {} }},", env_json(env))?;
+    }
+    if !osx_env.is_empty() {
+        let osx_env = env.iter().chain(osx_env.iter()).cloned().collect::<Vec<_>>();
+        writeln!(*o, "            \"osx\":              {{ \"options\": {{ \"env\": {} }} }},", env_json(&osx_env))?;
+    }
+    match group {
+        None                    => {},
+        Some((kind, false))     => writeln!(*o, "            \"group\":            {},", serde_json::to_string(kind).unwrap())?,
+        Some((kind, true))      => writeln!(*o, "            \"group\":            {{ \"kind\": {}, \"isDefault\": true }},", serde_json::to_string(kind).unwrap())?,
+    }
+    writeln!(*o, "            \"presentation\":     {{ \"clear\": true, \"panel\": \"shared\", \"reveal\": \"always\" }},")?;
+    writeln!(*o, "        }},")?;
+    Ok(())
+}
+
+/// Write a task running `cmd` after the `depends_on` task, e.g. to copy its output elsewhere.
+fn write_deploy(o: &mut impl io::Write, label: &str, cmd: &str, depends_on: &str) -> io::Result<()> {
     writeln!(*o, "        {{")?;
-    writeln!(*o, "            \"label\":            {},", cmd)?;
-    writeln!(*o, "            \"command\":          {},", cmd)?;
+    writeln!(*o, "            \"label\":            {},", serde_json::to_string(label).unwrap())?;
+    writeln!(*o, "            \"command\":          {},", serde_json::to_string(cmd).unwrap())?;
+    writeln!(*o, "            \"dependsOn\":        [ {} ],", serde_json::to_string(depends_on).unwrap())?;
     writeln!(*o, "            \"presentation\":     {{ \"clear\": true, \"panel\": \"shared\", \"reveal\": \"always\" }},")?;
     writeln!(*o, "        }},")?;
     Ok(())
 }
+
+/// `{ "NAME": "value", ... }`
+fn env_json(env: &[(String, String)]) -> String {
+    let vars = env.iter().map(|(name, value)| format!("{}: {}", serde_json::to_string(name).unwrap(), serde_json::to_string(value).unwrap())).collect::<Vec<_>>();
+    format!("{{ {} }}", vars.join(", "))
+}
+
+
+
+#[cfg(test)] mod tests {
+    use super::*;
+
+    #[test] fn quote_args() {
+        assert_eq!(quote_server_arg("-L"),                          "-L");
+        assert_eq!(quote_server_arg("/usr/a b"),                    "\"/usr/a b\"");
+        assert_eq!(quote_server_arg("${workspaceFolder}/a"),        "\"${workspaceFolder}/a\"");
+        assert_eq!(quote_server_arg(r#"C:\a b\ "q" \"#),            r#""C:\a b\ \"q\" \\""#);
+        assert_eq!(quote_server_arg(r#"a\"b"#),                     r#""a\\\"b""#);
+        assert_eq!(quote_server_arg(""),                            "\"\"");
+        assert_eq!(quote_sh_arg("./scripts/run.sh"),                "./scripts/run.sh");
+        assert_eq!(quote_sh_arg("${workspaceFolder}/it's"),         "'${workspaceFolder}/it'\\''s'");
+    }
+
+    #[test] fn runs_on_same_triple() {
+        assert!(runs_on("x86_64-unknown-linux-gnu", "x86_64-unknown-linux-gnu"));
+        assert!(runs_on("x86_64-unknown-linux-gnu", "x86_64-unknown-linux-musl"));
+        assert!(runs_on("aarch64-apple-darwin",     "aarch64-apple-darwin"));
+        assert!(runs_on("x86_64-pc-windows-msvc",   "x86_64-pc-windows-gnu"));
+    }
+
+    #[test] fn runs_on_32_bit_x86() {
+        assert!( runs_on("x86_64-unknown-linux-gnu", "i686-unknown-linux-gnu"));
+        assert!( runs_on("x86_64-unknown-linux-gnu", "i586-unknown-linux-gnu"));
+        assert!( runs_on("x86_64-pc-windows-msvc",   "i686-pc-windows-msvc"));
+        assert!(!runs_on("i686-unknown-linux-gnu",   "x86_64-unknown-linux-gnu"));
+        assert!(!runs_on("aarch64-unknown-linux-gnu", "i686-unknown-linux-gnu"));
+    }
+
+    #[test] fn runs_on_other_arch_or_os() {
+        assert!(!runs_on("x86_64-unknown-linux-gnu", "aarch64-unknown-linux-gnu"));
+        assert!(!runs_on("x86_64-unknown-linux-gnu", "x86_64-pc-windows-gnu"));
+        assert!(!runs_on("x86_64-pc-windows-msvc",   "x86_64-unknown-linux-gnu"));
+        assert!(!runs_on("x86_64-apple-darwin",      "x86_64-unknown-freebsd"));
+        assert!(!runs_on("x86_64-unknown-linux-gnu", "wasm32-unknown-unknown"));
+        assert!(!runs_on("aarch64-apple-darwin",     "aarch64-apple-ios"));
+    }
+}
@@ -0,0 +1,55 @@
+use std::io;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+
+
+/// `rustc --print sysroot`
+pub(crate) fn sysroot() -> io::Result<PathBuf> {
+    let o = Command::new("rustc").args(["--print", "sysroot"]).stderr(Stdio::inherit()).output()?;
+    match o.status.code() {
+        Some(0) => {},
+        Some(n) => return Err(io::Error::new(io::ErrorKind::BrokenPipe, format!("`rustc --print sysroot` failed (exit code {})", n))),
+        None    => return Err(io::Error::new(io::ErrorKind::BrokenPipe, "`rustc --print sysroot` failed (signal)")),
+    }
+    let stdout = String::from_utf8(o.stdout).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    Ok(PathBuf::from(stdout.trim()))
+}
+
+/// `rustc -vV`
+#[derive(Debug, Default)]
+pub(crate) struct VersionVerbose {
+    pub host:           String,
+    pub commit_hash:    Option<String>,
+}
+
+/// `rustc -vV`
+pub(crate) fn version_verbose() -> io::Result<VersionVerbose> {
+    let o = Command::new("rustc").args(["-vV"]).stderr(Stdio::inherit()).output()?;
+    match o.status.code() {
+        Some(0) => {},
+        Some(n) => return Err(io::Error::new(io::ErrorKind::BrokenPipe, format!("`rustc -vV` failed (exit code {})", n))),
+        None    => return Err(io::Error::new(io::ErrorKind::BrokenPipe, "`rustc -vV` failed (signal)")),
+    }
+    let stdout = String::from_utf8(o.stdout).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    let field = |name: &str| stdout.lines().find_map(|line| line.strip_prefix(name)?.strip_prefix(": ")).map(String::from);
+    Ok(VersionVerbose {
+        host:           field("host").ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "`rustc -vV` didn't specify a host"))?,
+        commit_hash:    field("commit-hash").filter(|hash| hash != "unknown"),
+    })
+}
+
+/// `rustc --print cfg [--target {triple}]` as `(name, value)`s, e.g. `("unix", None)`, `("target_os", Some("linux"))`
+pub(crate) fn cfg(triple: Option<&str>) -> io::Result<Vec<(String, Option<String>)>> {
+    let o = Command::new("rustc").args(["--print", "cfg"]).args(triple.map(|triple| ["--target", triple]).iter().flatten()).stderr(Stdio::inherit()).output()?;
+    match o.status.code() {
+        Some(0) => {},
+        Some(n) => return Err(io::Error::new(io::ErrorKind::BrokenPipe, format!("`rustc --print cfg` failed (exit code {})", n))),
+        None    => return Err(io::Error::new(io::ErrorKind::BrokenPipe, "`rustc --print cfg` failed (signal)")),
+    }
+    let stdout = String::from_utf8(o.stdout).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    Ok(stdout.lines().map(|line| match line.split_once('=') {
+        Some((name, value)) => (name.into(), Some(value.trim_matches('"').into())),
+        None                => (line.into(), None),
+    }).collect())
+}